@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::fs::read_to_string;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::output::Record;
+
+/// The expected answers of a day. A missing part is not checked.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expected {
+  pub part1: Option<String>,
+  pub part2: Option<String>,
+}
+
+/// Expected answers keyed by day, read from a TOML file such as:
+///
+/// ```toml
+/// [day1]
+/// part1 = "24000"
+/// part2 = "45000"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Answers(BTreeMap<usize, Expected>);
+
+impl Answers {
+  pub fn parse(string: &str) -> Result<Self, Box<dyn Error>> {
+    let tables: BTreeMap<String, Expected> = toml::from_str(string)?;
+
+    let answers = tables
+      .into_iter()
+      .map(|(key, expected)| {
+        let day = key
+          .strip_prefix("day")
+          .and_then(|day| day.parse().ok())
+          .ok_or_else(|| format!("Expected a table named dayN, received {key}"))?;
+
+        Ok((day, expected))
+      })
+      .collect::<Result<_, String>>()?;
+
+    Ok(Self(answers))
+  }
+
+  pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+    Self::parse(&read_to_string(path)?)
+  }
+
+  pub fn get(&self, day: usize) -> Option<&Expected> {
+    self.0.get(&day)
+  }
+}
+
+/// The outcome of comparing one part against its expected answer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Check {
+  Pass,
+  Fail { expected: String, actual: Option<String> },
+  Unknown,
+  /// The part has no expected answer and answered with nothing, which is how
+  /// a day marks a part it does not solve yet.
+  Unsolved,
+}
+
+impl Check {
+  fn new(expected: Option<&String>, actual: Option<&String>) -> Self {
+    match (expected, actual) {
+      (None, Some(actual)) if actual.is_empty() => Self::Unsolved,
+      (None, _) => Self::Unknown,
+      (Some(expected), Some(actual)) if normalize(expected) == normalize(actual) => Self::Pass,
+      (Some(expected), actual) => Self::Fail { expected: expected.clone(), actual: actual.cloned() },
+    }
+  }
+
+  pub fn is_fail(&self) -> bool {
+    matches!(self, Self::Fail { .. })
+  }
+}
+
+impl Display for Check {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::Pass => write!(f, "pass"),
+      Self::Unknown => write!(f, "no expected answer"),
+      Self::Unsolved => write!(f, "not solved yet"),
+      Self::Fail { expected, actual: Some(actual) } => write!(f, "FAIL (expected {expected}, received {actual})"),
+      Self::Fail { expected, actual: None } => write!(f, "FAIL (expected {expected}, received no answer)"),
+    }
+  }
+}
+
+/// Trailing whitespace is not significant, so that multi-line answers like the
+/// day 10 screen survive editors that strip it.
+fn normalize(answer: &str) -> Vec<&str> {
+  answer.trim_end().lines().map(str::trim_end).collect()
+}
+
+pub fn check(record: &Record, answers: &Answers) -> (Check, Check) {
+  let expected = answers.get(record.day).cloned().unwrap_or_default();
+
+  (
+    Check::new(expected.part1.as_ref(), record.part1.as_ref()),
+    Check::new(expected.part2.as_ref(), record.part2.as_ref()),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{check, Answers, Check, Expected};
+  use crate::output::Record;
+
+  const ANSWERS: &str = "[day1]\npart1 = \"24000\"\npart2 = \"45000\"\n\n[day10]\npart1 = \"13140\"\n";
+
+  fn record(day: usize, part1: &str, part2: &str) -> Record {
+    Record {
+      day,
+      title: "",
+      part1: Some(part1.to_string()),
+      part2: Some(part2.to_string()),
+      parse_us: None,
+      part1_us: None,
+      part2_us: None,
+      total_us: None,
+      error: None,
+    }
+  }
+
+  #[test]
+  fn test_parse() {
+    let answers = Answers::parse(ANSWERS).unwrap();
+    assert_eq!(answers.get(1), Some(&Expected { part1: Some(String::from("24000")), part2: Some(String::from("45000")) }));
+    assert_eq!(answers.get(10), Some(&Expected { part1: Some(String::from("13140")), part2: None }));
+    assert_eq!(answers.get(2), None);
+
+    assert!(Answers::parse("[one]\npart1 = \"1\"").is_err());
+    assert!(Answers::parse("[day1]\npart3 = \"1\"").is_err());
+  }
+
+  #[test]
+  fn test_check() {
+    let answers = Answers::parse(ANSWERS).unwrap();
+
+    assert_eq!(check(&record(1, "24000", "45000"), &answers), (Check::Pass, Check::Pass));
+    assert_eq!(check(&record(10, "13140", "###  \n#..#"), &answers), (Check::Pass, Check::Unknown));
+    assert_eq!(check(&record(2, "1", "2"), &answers), (Check::Unknown, Check::Unknown));
+    assert_eq!(check(&record(2, "", "2"), &answers), (Check::Unsolved, Check::Unknown));
+    assert!(!Check::Unsolved.is_fail());
+
+    // A part that stops answering fails once its answer is recorded.
+    let (_, part2) = check(&record(1, "24000", ""), &answers);
+    assert_eq!(part2, Check::Fail { expected: String::from("45000"), actual: Some(String::new()) });
+
+    let (part1, part2) = check(&record(1, "24001", "45000  \n"), &answers);
+    assert_eq!(part1, Check::Fail { expected: String::from("24000"), actual: Some(String::from("24001")) });
+    assert!(part1.is_fail());
+    assert_eq!(part2, Check::Pass);
+  }
+}
@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::{read_to_string, write};
+use std::hint::black_box;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::solution::{catch_panic, SolveResult, Solver};
+
+/// Summary statistics over repeated runs of one step, in microseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+  pub min: f64,
+  pub median: f64,
+  pub p95: f64,
+  pub mean: f64,
+  pub stddev: f64,
+}
+
+impl Stats {
+  pub fn from_samples(samples: &[Duration]) -> Self {
+    assert!(!samples.is_empty(), "Cannot compute statistics without samples");
+
+    let mut micros = samples.iter().map(|sample| sample.as_secs_f64() * 1e6).collect::<Vec<_>>();
+    micros.sort_by(|a, b| a.total_cmp(b));
+
+    let n = micros.len();
+    let median = if n % 2 == 0 {
+      (micros[n / 2 - 1] + micros[n / 2]) / 2.0
+    } else {
+      micros[n / 2]
+    };
+    let p95 = micros[((n as f64) * 0.95).ceil() as usize - 1];
+    let mean = micros.iter().sum::<f64>() / n as f64;
+    let variance = if n > 1 {
+      micros.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1) as f64
+    } else {
+      0.0
+    };
+
+    Self { min: micros[0], median, p95, mean, stddev: variance.sqrt() }
+  }
+}
+
+/// The statistics of parsing and of both parts of a day.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DayBench {
+  pub parse: Stats,
+  pub part1: Stats,
+  pub part2: Stats,
+}
+
+impl DayBench {
+  pub fn steps(&self) -> [(&'static str, &Stats); 3] {
+    [("parse", &self.parse), ("part1", &self.part1), ("part2", &self.part2)]
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+  pub warmup: usize,
+  pub iterations: usize,
+}
+
+impl Default for Options {
+  fn default() -> Self {
+    Self { warmup: 10, iterations: 100 }
+  }
+}
+
+fn sample<T>(iterations: usize, mut f: impl FnMut() -> SolveResult<T>) -> SolveResult<Vec<Duration>> {
+  (0..iterations)
+    .map(|_| {
+      let start = Instant::now();
+      black_box(f()?);
+      Ok(start.elapsed())
+    })
+    .collect()
+}
+
+/// Times every step of `solver` on an input that has already been read, so
+/// that file I/O is not part of the measurements.
+pub fn bench(solver: &dyn Solver, input: &str, options: Options) -> SolveResult<DayBench> {
+  catch_panic(|| measure(solver, input, options))
+}
+
+fn measure(solver: &dyn Solver, input: &str, options: Options) -> SolveResult<DayBench> {
+  let iterations = options.iterations.max(1);
+
+  for _ in 0..options.warmup {
+    let parsed = solver.parse(input)?;
+    black_box(solver.part1(parsed.as_ref())?);
+    black_box(solver.part2(parsed.as_ref())?);
+  }
+
+  let parse = sample(iterations, || solver.parse(black_box(input)))?;
+  let parsed = solver.parse(input)?;
+  let part1 = sample(iterations, || solver.part1(black_box(parsed.as_ref())))?;
+  let part2 = sample(iterations, || solver.part2(black_box(parsed.as_ref())))?;
+
+  Ok(DayBench {
+    parse: Stats::from_samples(&parse),
+    part1: Stats::from_samples(&part1),
+    part2: Stats::from_samples(&part2),
+  })
+}
+
+/// Benchmark results keyed by day, stored as JSON.
+pub type Baseline = BTreeMap<usize, DayBench>;
+
+pub fn load_baseline(path: &Path) -> Result<Baseline, Box<dyn Error>> {
+  Ok(serde_json::from_str(&read_to_string(path)?)?)
+}
+
+pub fn save_baseline(path: &Path, baseline: &Baseline) -> Result<(), Box<dyn Error>> {
+  write(path, serde_json::to_string_pretty(baseline)?)?;
+  Ok(())
+}
+
+/// The relative change of the median against the baseline, in percent.
+pub fn change(current: &Stats, baseline: &Stats) -> f64 {
+  if baseline.median == 0.0 {
+    return 0.0;
+  }
+
+  (current.median - baseline.median) / baseline.median * 100.0
+}
+
+/// Whether the median got slower than the baseline by more than `threshold`
+/// percent.
+pub fn is_regression(current: &Stats, baseline: &Stats, threshold: f64) -> bool {
+  change(current, baseline) > threshold
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use super::{bench, change, is_regression, Options, Stats};
+  use crate::day1::Day1;
+
+  fn micros(samples: &[u64]) -> Vec<Duration> {
+    samples.iter().map(|us| Duration::from_micros(*us)).collect()
+  }
+
+  #[test]
+  fn test_stats() {
+    let stats = Stats::from_samples(&micros(&[5, 1, 4, 2, 3]));
+    assert_eq!((stats.min, stats.median, stats.p95, stats.mean), (1.0, 3.0, 5.0, 3.0));
+    assert!((stats.stddev - 2.5f64.sqrt()).abs() < 1e-9);
+
+    let stats = Stats::from_samples(&micros(&[4, 1, 3, 2]));
+    assert_eq!(stats.median, 2.5);
+
+    let samples = micros(&(1..=100).collect::<Vec<_>>());
+    assert_eq!(Stats::from_samples(&samples).p95, 95.0);
+
+    let stats = Stats::from_samples(&micros(&[7]));
+    assert_eq!((stats.min, stats.median, stats.p95, stats.stddev), (7.0, 7.0, 7.0, 0.0));
+  }
+
+  #[test]
+  fn test_regression() {
+    let baseline = Stats::from_samples(&micros(&[100]));
+    let slower = Stats::from_samples(&micros(&[115]));
+    let faster = Stats::from_samples(&micros(&[80]));
+
+    assert_eq!(change(&slower, &baseline).round(), 15.0);
+    assert!(is_regression(&slower, &baseline, 10.0));
+    assert!(!is_regression(&slower, &baseline, 20.0));
+    assert!(!is_regression(&faster, &baseline, 10.0));
+  }
+
+  #[test]
+  fn test_bench() {
+    let result = bench(&Day1, "1000\n2000\n\n3000", Options { warmup: 1, iterations: 5 }).unwrap();
+    assert!(result.steps().iter().all(|(_, stats)| stats.min <= stats.median && stats.median <= stats.p95));
+
+    assert!(bench(&Day1, "", Options { warmup: 0, iterations: 5 }).is_err());
+  }
+}
@@ -0,0 +1,413 @@
+use std::env;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::solution::SolveResult;
+use crate::submit::Outcome;
+use crate::utils::{input_file, Input, SolveError};
+
+/// The puzzle year requested from the server.
+pub const YEAR: usize = 2022;
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+/// The file the session token is read from when `AOC_SESSION` is not set and
+/// `AOC_SESSION_FILE` does not name another one.
+pub const SESSION_FILE: &str = ".aoc-session";
+
+/// The shortest time between two requests to the server.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+
+const USER_AGENT: &str = concat!("aoc2022/", env!("CARGO_PKG_VERSION"));
+
+/// Why a request to the puzzle server failed.
+#[derive(Debug)]
+pub enum ClientError {
+  /// No session token was found in the environment or the session file.
+  MissingSession,
+  /// The server refused the session token.
+  Unauthorized,
+  /// The puzzle of the day is not unlocked, or does not exist.
+  NotAvailable(usize),
+  /// The server asked to slow down, optionally saying for how many seconds.
+  RateLimited(Option<u64>),
+  Status(u16, String),
+  Transport(String),
+  Io(io::Error),
+}
+
+impl Display for ClientError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::MissingSession => write!(f, "No session token found, set AOC_SESSION or write it to {SESSION_FILE}"),
+      Self::Unauthorized => write!(f, "The server rejected the session token, it may have expired"),
+      Self::NotAvailable(day) => write!(f, "The puzzle of day {day} is not available yet"),
+      Self::RateLimited(Some(seconds)) => write!(f, "Rate limited by the server, retry in {seconds} seconds"),
+      Self::RateLimited(None) => write!(f, "Rate limited by the server, retry later"),
+      Self::Status(status, message) => write!(f, "The server answered with status {status}: {message}"),
+      Self::Transport(message) => write!(f, "Failed to reach the server: {message}"),
+      Self::Io(source) => Display::fmt(source, f),
+    }
+  }
+}
+
+impl Error for ClientError {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match self {
+      Self::Io(source) => Some(source),
+      _ => None,
+    }
+  }
+}
+
+impl From<io::Error> for ClientError {
+  fn from(source: io::Error) -> Self {
+    Self::Io(source)
+  }
+}
+
+/// A client for an Advent of Code compatible server, downloading inputs and
+/// submitting answers.
+///
+/// Requests made through the same client are spaced by at least its interval,
+/// also when the client is shared between threads.
+pub struct Client {
+  base_url: String,
+  session: String,
+  interval: Duration,
+  agent: ureq::Agent,
+  last_request: Mutex<Option<Instant>>,
+}
+
+/// Leaves out the session token, which must not end up in logs.
+impl fmt::Debug for Client {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("Client")
+      .field("base_url", &self.base_url)
+      .field("session", &"<redacted>")
+      .field("interval", &self.interval)
+      .field("last_request", &self.last_request)
+      .finish_non_exhaustive()
+  }
+}
+
+impl Client {
+  pub fn new(base_url: impl Into<String>, session: impl Into<String>) -> Self {
+    Self {
+      base_url: base_url.into().trim_end_matches('/').to_string(),
+      session: session.into(),
+      interval: DEFAULT_INTERVAL,
+      agent: ureq::AgentBuilder::new().timeout(Duration::from_secs(30)).user_agent(USER_AGENT).build(),
+      last_request: Mutex::new(None),
+    }
+  }
+
+  pub fn with_interval(mut self, interval: Duration) -> Self {
+    self.interval = interval;
+    self
+  }
+
+  /// A client configured from the environment. The base URL defaults to
+  /// `AOC_BASE_URL`, then [`DEFAULT_BASE_URL`]. The session token is read from
+  /// `AOC_SESSION`, or else from the file named by `AOC_SESSION_FILE`, or
+  /// else from [`SESSION_FILE`] in the working directory.
+  pub fn from_env(base_url: Option<String>) -> Result<Self, ClientError> {
+    let base_url = base_url
+      .or_else(|| env::var("AOC_BASE_URL").ok())
+      .unwrap_or_else(|| String::from(DEFAULT_BASE_URL));
+
+    Ok(Self::new(base_url, session_from_env()?))
+  }
+
+  pub fn base_url(&self) -> &str {
+    &self.base_url
+  }
+
+  /// Downloads the puzzle input of `day`.
+  pub fn download(&self, day: usize) -> Result<String, ClientError> {
+    let url = format!("{}/{YEAR}/day/{day}/input", self.base_url);
+
+    self.throttle();
+
+    let response = self.agent.get(&url).set("Cookie", &format!("session={}", self.session)).call();
+
+    match response {
+      Ok(response) => Ok(response.into_string()?),
+      Err(ureq::Error::Status(404, _)) => Err(ClientError::NotAvailable(day)),
+      Err(error) => Err(request_error(error)),
+    }
+  }
+
+  /// Submits `answer` to `part` of `day` and returns the server's judgement.
+  pub fn submit(&self, day: usize, part: usize, answer: &str) -> Result<Outcome, ClientError> {
+    let url = format!("{}/{YEAR}/day/{day}/answer", self.base_url);
+
+    self.throttle();
+
+    let response = self
+      .agent
+      .post(&url)
+      .set("Cookie", &format!("session={}", self.session))
+      .send_form(&[("level", &part.to_string()), ("answer", answer)]);
+
+    match response {
+      Ok(response) => Ok(Outcome::parse(&response.into_string()?)),
+      Err(ureq::Error::Status(404, _)) => Err(ClientError::NotAvailable(day)),
+      Err(error) => Err(request_error(error)),
+    }
+  }
+
+  /// The input of `day` cached in `dir`, downloaded first when it is missing
+  /// or `force` is set. Returns the path of the cached file and whether it
+  /// was downloaded.
+  pub fn fetch(&self, dir: &Path, day: usize, force: bool) -> Result<(PathBuf, bool), ClientError> {
+    let path = dir.join(input_file(day));
+
+    if !force && path.is_file() {
+      return Ok((path, false));
+    }
+
+    let input = self.download(day)?;
+
+    // Write next to the target first, so that an interrupted download never
+    // leaves a truncated input behind.
+    fs::create_dir_all(dir)?;
+    let partial = path.with_extension("txt.part");
+    fs::write(&partial, input)?;
+    fs::rename(&partial, &path)?;
+
+    Ok((path, true))
+  }
+
+  /// Waits until the interval since the previous request has passed.
+  fn throttle(&self) {
+    let mut last_request = self.last_request.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    if let Some(wait) = last_request.and_then(|last| self.interval.checked_sub(last.elapsed())) {
+      thread::sleep(wait);
+    }
+
+    *last_request = Some(Instant::now());
+  }
+}
+
+/// Reads the input of `day` like [`Input::read`]. When the input is a
+/// directory that lacks the file of `day`, the file is downloaded into it
+/// first if a client is given.
+pub fn read_or_fetch(input: &Input, day: usize, client: Option<&Client>) -> SolveResult<String> {
+  let dir = match input {
+    Input::Path(dir) if dir.is_dir() && !dir.join(input_file(day)).exists() => dir,
+    _ => return input.read(day),
+  };
+
+  let error = match client {
+    Some(client) => match client.fetch(dir, day, false) {
+      Ok(_) => return input.read(day),
+      Err(error) => io::Error::other(error),
+    },
+    None => io::Error::new(io::ErrorKind::NotFound, format!("Missing input, run `fetch {day}` with a session token to download it")),
+  };
+
+  Err(SolveError::from(error).in_file(input.name(day)))
+}
+
+fn session_from_env() -> Result<String, ClientError> {
+  if let Some(session) = env::var("AOC_SESSION").ok().filter(|session| !session.trim().is_empty()) {
+    return Ok(session.trim().to_string());
+  }
+
+  let path = env::var_os("AOC_SESSION_FILE").map_or_else(|| PathBuf::from(SESSION_FILE), PathBuf::from);
+
+  match fs::read_to_string(&path) {
+    Ok(session) if !session.trim().is_empty() => Ok(session.trim().to_string()),
+    Ok(_) => Err(ClientError::MissingSession),
+    Err(error) if error.kind() == io::ErrorKind::NotFound => Err(ClientError::MissingSession),
+    Err(error) => Err(ClientError::Io(io::Error::new(error.kind(), format!("{}: {error}", path.display())))),
+  }
+}
+
+fn request_error(error: ureq::Error) -> ClientError {
+  match error {
+    ureq::Error::Status(400 | 401 | 403, _) => ClientError::Unauthorized,
+    ureq::Error::Status(429, response) => {
+      ClientError::RateLimited(response.header("Retry-After").and_then(|seconds| seconds.trim().parse().ok()))
+    },
+    ureq::Error::Status(status, response) => {
+      let message = response.into_string().unwrap_or_default();
+      ClientError::Status(status, message.lines().next().unwrap_or_default().trim().to_string())
+    },
+    ureq::Error::Transport(transport) => ClientError::Transport(transport.to_string()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::io::{BufRead, BufReader, Read, Write};
+  use std::net::TcpListener;
+  use std::thread::{self, JoinHandle};
+  use std::time::{Duration, Instant};
+  use std::{fs, process};
+
+  use super::{read_or_fetch, Client, ClientError};
+  use crate::submit::Outcome;
+  use crate::utils::Input;
+
+  #[derive(Debug, Clone, PartialEq)]
+  struct Request {
+    line: String,
+    cookie: String,
+    body: String,
+  }
+
+  /// Serves one canned response per connection and returns the requests it
+  /// received.
+  fn serve(responses: Vec<&'static str>) -> (String, JoinHandle<Vec<Request>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    let server = thread::spawn(move || {
+      responses
+        .into_iter()
+        .map(|response| {
+          let (mut stream, _) = listener.accept().unwrap();
+          let mut reader = BufReader::new(stream.try_clone().unwrap());
+          let (mut line, mut cookie, mut length) = (String::new(), String::new(), 0);
+          reader.read_line(&mut line).unwrap();
+
+          loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).unwrap();
+
+            let Some((name, value)) = header.trim_end().split_once(": ") else {
+              break;
+            };
+
+            match name.to_ascii_lowercase().as_str() {
+              "cookie" => cookie = value.to_string(),
+              "content-length" => length = value.parse().unwrap(),
+              _ => (),
+            }
+          }
+
+          let mut body = vec![0; length];
+          reader.read_exact(&mut body).unwrap();
+
+          stream.write_all(response.as_bytes()).unwrap();
+          Request { line: line.trim_end().to_string(), cookie, body: String::from_utf8(body).unwrap() }
+        })
+        .collect()
+    });
+
+    (url, server)
+  }
+
+  #[test]
+  fn test_fetch_caches_the_input() {
+    let (url, server) = serve(vec![
+      "HTTP/1.1 200 OK\r\nContent-Length: 6\r\nConnection: close\r\n\r\n1\n2\n3\n",
+      "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n4\n",
+    ]);
+    let client = Client::new(format!("{url}/"), "secret").with_interval(Duration::ZERO);
+
+    let dir = std::env::temp_dir().join(format!("aoc2022-fetch-{}", process::id()));
+    let _ = fs::remove_dir_all(&dir);
+
+    let (path, downloaded) = client.fetch(&dir, 3, false).unwrap();
+    assert!(downloaded);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "1\n2\n3\n");
+
+    // A cached input is not downloaded again unless forced.
+    assert_eq!(client.fetch(&dir, 3, false).unwrap(), (path.clone(), false));
+
+    assert_eq!(client.fetch(&dir, 3, true).unwrap(), (path.clone(), true));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "4\n");
+
+    let requests = server.join().unwrap();
+    let request = Request { line: String::from("GET /2022/day/3/input HTTP/1.1"), cookie: String::from("session=secret"), body: String::new() };
+    assert_eq!(requests, vec![request; 2]);
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn test_read_or_fetch() {
+    let (url, server) = serve(vec!["HTTP/1.1 200 OK\r\nContent-Length: 3\r\nConnection: close\r\n\r\n42\n"]);
+    let client = Client::new(url, "secret").with_interval(Duration::ZERO);
+
+    let dir = std::env::temp_dir().join(format!("aoc2022-read-{}", process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let input = Input::Path(dir.clone());
+
+    let error = read_or_fetch(&input, 7, None).unwrap_err();
+    assert!(error.to_string().ends_with("day7.txt: Missing input, run `fetch 7` with a session token to download it"));
+
+    assert_eq!(read_or_fetch(&input, 7, Some(&client)).unwrap(), "42\n");
+    // The cached input is read without asking the server again.
+    assert_eq!(read_or_fetch(&input, 7, Some(&client)).unwrap(), "42\n");
+
+    server.join().unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn test_submit() {
+    let (url, server) = serve(vec![
+      "HTTP/1.1 200 OK\r\nContent-Length: 59\r\nConnection: close\r\n\r\n<p>That's not the right answer; your answer is too low.</p>",
+      "HTTP/1.1 200 OK\r\nContent-Length: 31\r\nConnection: close\r\n\r\n<p>That's the right answer!</p>",
+    ]);
+    let client = Client::new(url, "secret").with_interval(Duration::ZERO);
+
+    assert_eq!(client.submit(5, 2, "12").unwrap(), Outcome::TooLow);
+    assert_eq!(client.submit(5, 2, "C D").unwrap(), Outcome::Correct);
+
+    let requests = server.join().unwrap();
+    assert_eq!(requests[0].line, "POST /2022/day/5/answer HTTP/1.1");
+    assert_eq!(requests[0].cookie, "session=secret");
+    assert_eq!((requests[0].body.as_str(), requests[1].body.as_str()), ("level=2&answer=12", "level=2&answer=C+D"));
+  }
+
+  #[test]
+  fn test_debug_hides_the_session() {
+    let client = Client::new("http://localhost", "53616c7465645f5f");
+    let debug = format!("{client:?}");
+
+    assert!(!debug.contains("53616c7465645f5f"), "{debug}");
+    assert!(debug.contains("session: \"<redacted>\""), "{debug}");
+    assert!(debug.contains("http://localhost"), "{debug}");
+  }
+
+  #[test]
+  fn test_throttle() {
+    let client = Client::new("http://localhost", "secret").with_interval(Duration::from_millis(50));
+
+    let start = Instant::now();
+    client.throttle();
+    client.throttle();
+    assert!(start.elapsed() >= Duration::from_millis(50));
+  }
+
+  #[test]
+  fn test_download_errors() {
+    let (url, server) = serve(vec![
+      "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+      "HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+      "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 60\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+      "HTTP/1.1 500 Internal Server Error\r\nContent-Length: 5\r\nConnection: close\r\n\r\nOops\n",
+    ]);
+    let client = Client::new(url, "secret").with_interval(Duration::ZERO);
+
+    assert!(matches!(client.download(25), Err(ClientError::NotAvailable(25))));
+    assert!(matches!(client.download(1), Err(ClientError::Unauthorized)));
+    assert!(matches!(client.download(1), Err(ClientError::RateLimited(Some(60)))));
+    assert_eq!(client.download(1).unwrap_err().to_string(), "The server answered with status 500: Oops");
+
+    server.join().unwrap();
+  }
+}
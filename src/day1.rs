@@ -1,42 +1,47 @@
-use std::error::Error;
+use crate::solution::{Solution, SolveResult};
+use crate::utils::{parse_number, SolveError};
 
-use crate::utils::{read_input_lines, SolveError};
+pub struct Day1;
 
-pub fn solve() -> Result<(String, String), Box<dyn Error>> {
-  let lines: Vec<Result<String, std::io::Error>> = read_input_lines("day1.txt")?.collect();
+impl Solution for Day1 {
+  type Parsed = Vec<u32>;
 
-  let elves = accumulate_elves(lines);
+  const DAY: usize = 1;
+  const TITLE: &'static str = "Calorie Counting";
 
-  let max = elves.iter().max();
+  fn parse(input: &str) -> SolveResult<Self::Parsed> {
+    accumulate_elves(input)
+  }
 
-  let mut top_three = elves.clone();
-  top_three.sort_by(|a, b| b.partial_cmp(a).unwrap());
-  let top_three: u32 = top_three.iter().take(3).sum();
+  fn part1(elves: &Self::Parsed) -> SolveResult<String> {
+    let Some(max) = elves.iter().max() else {
+      return Err(SolveError::logic("Failed to calculate max calories"));
+    };
 
-  if let Some(max) = max {
-    return Ok(((*max).to_string(), top_three.to_string()));
+    Ok(max.to_string())
   }
 
-  Err(Box::new(SolveError::new("Failed to calculate max calories"))) 
+  fn part2(elves: &Self::Parsed) -> SolveResult<String> {
+    let mut top_three = elves.clone();
+    top_three.sort_by(|a, b| b.partial_cmp(a).unwrap());
+    let top_three: u32 = top_three.iter().take(3).sum();
+
+    Ok(top_three.to_string())
+  }
 }
 
-fn accumulate_elves(lines: Vec<Result<String, std::io::Error>>) -> Vec<u32> {
-  lines.into_iter().fold(Vec::new(), |mut v,r| {
-    let s = r.unwrap();
-    if s.len() == 0 { 
+pub fn accumulate_elves(input: &str) -> SolveResult<Vec<u32>> {
+  input.lines().try_fold(Vec::new(), |mut v, s| {
+    if s.is_empty() {
       v.push(0);
-      return v
+      return Ok(v)
     };
-    if let Ok(n) = s.parse::<u32>() {
-      if v.len() == 0 {
-        v.push(n);
-        return v;
-      }
-      let i = v.len() - 1;
-      v[i] += n;
-      return v
+    let n = parse_number::<u32>(input, s)?;
+    match v.last_mut() {
+      Some(last) => *last += n,
+      None => v.push(n),
     }
-    v
+    Ok(v)
   })
 }
 
@@ -46,18 +51,14 @@ mod tests {
 
   #[test]
   fn test_accumulate_elves() {
-    let lines = "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000"
-      .split('\n')
-      .map(|x| Ok(x.to_string()))
-      .collect();
-      
-    assert_eq!(accumulate_elves(lines), vec![6000, 4000, 11000, 24000, 10000]);
-
-    let lines = "\n"
-      .split('\n')
-      .map(|x| Ok(x.to_string()))
-      .collect();
-    assert_eq!(accumulate_elves(lines), vec![0, 0]);
+    let input = "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000";
+
+    assert_eq!(accumulate_elves(input).unwrap(), vec![6000, 4000, 11000, 24000, 10000]);
+
+    assert_eq!(accumulate_elves("\n\n").unwrap(), vec![0, 0]);
+
+    let error = accumulate_elves("1000\n2000\nabc\n\n4000").unwrap_err();
+    assert_eq!(error.to_string(), "<input>:3:1: Expected a number, found \"abc\"");
   }
 }
 
@@ -1,17 +1,16 @@
 use lazy_static::lazy_static;
 use regex::Regex;
 
-use std::error::Error;
+use crate::solution::{Solution, SolveResult};
+use crate::utils::{parse_lines, parse_number, SolveError};
 
-use crate::utils::read_input_lines_ok;
-
-#[derive(PartialEq, Debug)]
-enum Instruction {
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Instruction {
   Noop,
   Addx(i32),
 }
 
-const REGEX_PATTERN: &str = r"^(?P<noop>noop)|(?P<addx>addx (?P<value>-?\d+))$";
+const REGEX_PATTERN: &str = r"^(?:(?P<noop>noop)|(?P<addx>addx (?P<value>-?\d+)))$";
 
 lazy_static! {
   static ref REGEX: Regex = Regex::new(REGEX_PATTERN).unwrap();
@@ -20,28 +19,43 @@ lazy_static! {
 const LIT: char = '🎁';
 const DARK: char = '🎄';
 
-pub fn solve() -> Result<(String, String), Box<dyn Error>> {
-  let lines = read_input_lines_ok("day10.txt")?;
+pub struct Day10;
 
-  let (_, _, part1) = lines
-    .iter()
-    .map(parse_line)
-    .fold((0, 1, 0), step_instruction);
+impl Solution for Day10 {
+  type Parsed = Vec<Instruction>;
 
-  let mut part2 = String::from("See below ↓");
+  const DAY: usize = 10;
+  const TITLE: &'static str = "Cathode-Ray Tube";
 
-  let (_, _, output) = lines
-    .iter()
-    .map(parse_line)
-    .fold((0, 1, String::with_capacity(240)), draw_cycle);
-  
-  part2.push_str(&output);
-  part2.push_str("\n                                 ");
-  
-  Ok((part1.to_string(), part2))
+  fn parse(input: &str) -> SolveResult<Self::Parsed> {
+    parse_lines(input, parse_line)
+  }
+
+  fn part1(instructions: &Self::Parsed) -> SolveResult<String> {
+    let (_, _, part1) = instructions
+      .iter()
+      .copied()
+      .fold((0, 1, 0), step_instruction);
+
+    Ok(part1.to_string())
+  }
+
+  fn part2(instructions: &Self::Parsed) -> SolveResult<String> {
+    let mut part2 = String::from("See below ↓");
+
+    let (_, _, output) = instructions
+      .iter()
+      .copied()
+      .fold((0, 1, String::with_capacity(240)), draw_cycle);
+
+    part2.push_str(&output);
+    part2.push_str("\n                                 ");
+
+    Ok(part2)
+  }
 }
 
-fn draw_cycle((mut cycle, x_reg, mut output): (i32, i32, String), instruction: Instruction) -> (i32, i32, String) {
+pub fn draw_cycle((mut cycle, x_reg, mut output): (i32, i32, String), instruction: Instruction) -> (i32, i32, String) {
   match instruction {
     Instruction::Noop => {
       draw(cycle, x_reg, &mut output);
@@ -69,7 +83,7 @@ fn draw(cycle: i32, x_reg: i32, output: &mut String) {
   }
 }
 
-fn step_instruction((mut cycle, x_reg, mut sum): (i32, i32, i32), instr: Instruction) -> (i32, i32, i32) {
+pub fn step_instruction((mut cycle, x_reg, mut sum): (i32, i32, i32), instr: Instruction) -> (i32, i32, i32) {
   match instr {
     Instruction::Noop => {
       (cycle + 1, x_reg, get_sum(cycle + 1, x_reg, sum))
@@ -92,19 +106,20 @@ fn get_sum(cycle: i32, x_reg: i32, sum: i32) -> i32 {
   }
 }
 
-fn parse_line(line: &String) -> Instruction {
-  let captures = REGEX.captures(line).unwrap();
+pub fn parse_line(line: &str) -> SolveResult<Instruction> {
+  let invalid = || SolveError::parse(line, line, "Expected an instruction noop or addx V");
+
+  let captures = REGEX.captures(line).ok_or_else(invalid)?;
 
-  if let Some(_) = captures.name("noop") {
-    return Instruction::Noop;
+  if captures.name("noop").is_some() {
+    return Ok(Instruction::Noop);
   }
 
-  if let Some(_) = captures.name("addx") {
-    let value = captures.name("value").unwrap().as_str().parse().unwrap();
-    return Instruction::Addx(value);
+  if let Some(value) = captures.name("value") {
+    return Ok(Instruction::Addx(parse_number(line, value.as_str())?));
   }
 
-  panic!("Failed to parse line, received: {}", line);
+  Err(invalid())
 }
 
 #[cfg(test)]
@@ -141,9 +156,15 @@ mod tests {
   
   #[test]
   fn test_parse_line() {
-    assert_eq!(parse_line(&String::from("noop")), Noop);
+    assert_eq!(parse_line(&String::from("noop")), Ok(Noop));
     (-101..101).for_each(|value| {
-      assert_eq!(parse_line(&format!("addx {}", value)), Addx(value));
+      assert_eq!(parse_line(&format!("addx {}", value)), Ok(Addx(value)));
     });
+
+    assert!(parse_line("addx").is_err());
+    assert!(parse_line("noop garbage").is_err());
+    assert!(parse_line("say addx 1").is_err());
+    let error = parse_line("addx 9999999999").unwrap_err();
+    assert_eq!(error.to_string(), "<input>:1:6: Expected a number, found \"9999999999\"");
   }
 }
\ No newline at end of file
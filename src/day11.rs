@@ -1,135 +1,530 @@
 use lazy_static::lazy_static;
+use num_bigint::BigUint;
 use regex::Regex;
 
-use std::{error::Error, collections::VecDeque};
+use std::collections::VecDeque;
+use std::fmt::{self, Display};
+use std::iter::Peekable;
 
-use crate::utils::read_input_string;
+use crate::solution::{Solution, SolveResult};
+use crate::utils::{parse_number, SolveError};
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Operator {
+  Add,
+  Sub,
+  Mul,
+  Div,
+}
+
+impl Operator {
+  fn from_token(token: &str) -> Option<Self> {
+    match token {
+      "+" => Some(Self::Add),
+      "-" => Some(Self::Sub),
+      "*" => Some(Self::Mul),
+      "/" => Some(Self::Div),
+      _ => None,
+    }
+  }
+
+  /// Multiplication and division bind tighter than addition and subtraction.
+  fn precedence(self) -> u8 {
+    match self {
+      Self::Add | Self::Sub => 1,
+      Self::Mul | Self::Div => 2,
+    }
+  }
+}
+
+impl Display for Operator {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let symbol = match self {
+      Self::Add => "+",
+      Self::Sub => "-",
+      Self::Mul => "*",
+      Self::Div => "/",
+    };
+
+    write!(f, "{symbol}")
+  }
+}
+
+const OVERFLOW: &str = "overflows";
+const BELOW_ZERO: &str = "goes below zero";
+const DIVISION_BY_ZERO: &str = "divides by zero";
+
+/// The right-hand side of `new = ...`: the old worry level, integer literals
+/// and the four operators, evaluated from left to right with the usual
+/// precedence.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expression {
+  Old,
+  Literal(u64),
+  Binary(Box<Expression>, Operator, Box<Expression>),
+}
+
+impl Expression {
+  /// The new worry level computed from `old`. Fails with the reason when it
+  /// overflows, goes below zero or divides by zero.
+  pub fn evaluate(&self, old: u64) -> Result<u64, &'static str> {
+    match self {
+      Self::Old => Ok(old),
+      Self::Literal(n) => Ok(*n),
+      Self::Binary(left, operator, right) => {
+        let (left, right) = (left.evaluate(old)?, right.evaluate(old)?);
+
+        match operator {
+          Operator::Add => left.checked_add(right).ok_or(OVERFLOW),
+          Operator::Sub => left.checked_sub(right).ok_or(BELOW_ZERO),
+          Operator::Mul => left.checked_mul(right).ok_or(OVERFLOW),
+          Operator::Div => left.checked_div(right).ok_or(DIVISION_BY_ZERO),
+        }
+      },
+    }
+  }
+
+  /// The new worry level modulo `modulus`, computed from `old` modulo
+  /// `modulus`. Division has no such counterpart, and the sign of a
+  /// difference is lost, so both fail.
+  pub fn evaluate_modulo(&self, old: u64, modulus: u64) -> Result<u64, &'static str> {
+    match self {
+      Self::Old => Ok(old % modulus),
+      Self::Literal(n) => Ok(n % modulus),
+      Self::Binary(left, operator, right) => {
+        let (left, right) = (left.evaluate_modulo(old, modulus)?, right.evaluate_modulo(old, modulus)?);
+
+        let value = match operator {
+          Operator::Add => left.checked_add(right),
+          Operator::Mul => left.checked_mul(right),
+          Operator::Sub => return Err("cannot subtract modulo the tests"),
+          Operator::Div => return Err("cannot divide modulo the tests"),
+        };
+
+        value.map(|value| value % modulus).ok_or(OVERFLOW)
+      },
+    }
+  }
+
+  /// The new worry level computed exactly from `old`, which never overflows.
+  pub fn evaluate_exact(&self, old: &BigUint) -> Result<BigUint, &'static str> {
+    match self {
+      Self::Old => Ok(old.clone()),
+      Self::Literal(n) => Ok(BigUint::from(*n)),
+      Self::Binary(left, operator, right) => {
+        let (left, right) = (left.evaluate_exact(old)?, right.evaluate_exact(old)?);
+
+        match operator {
+          Operator::Add => Ok(left + right),
+          Operator::Sub if left < right => Err(BELOW_ZERO),
+          Operator::Sub => Ok(left - right),
+          Operator::Mul => Ok(left * right),
+          Operator::Div if right == BigUint::ZERO => Err(DIVISION_BY_ZERO),
+          Operator::Div => Ok(left / right),
+        }
+      },
+    }
+  }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
-enum Operation {
-  Mul(u64),
-  Add(u64),
-  Square,
+  /// Whether `operator` appears anywhere in the expression.
+  pub fn uses(&self, operator: Operator) -> bool {
+    match self {
+      Self::Binary(left, used, right) => *used == operator || left.uses(operator) || right.uses(operator),
+      _ => false,
+    }
+  }
+}
+
+impl Display for Expression {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::Old => write!(f, "old"),
+      Self::Literal(n) => write!(f, "{n}"),
+      Self::Binary(left, operator, right) => write!(f, "{left} {operator} {right}"),
+    }
+  }
+}
+
+/// How the worry levels are kept manageable after each inspection.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Relief {
+  /// Divided by three, as in part 1.
+  Divide,
+  /// Kept modulo the least common multiple of the tests, as in part 2.
+  Modulo(u64),
 }
 
 #[derive(Clone, Debug, PartialEq)]
-struct Monkey {
-  items: VecDeque<u64>,
-  operation: Operation,
-  test: u64,
-  if_true: usize,
-  if_false: usize,
-  activity: usize,
+pub struct Monkey {
+  pub items: VecDeque<u64>,
+  pub operation: Expression,
+  pub test: u64,
+  pub if_true: usize,
+  pub if_false: usize,
+  pub activity: usize,
 }
 
 impl Monkey {
-  fn inspect<const PART2: bool>(&mut self, lcm: u64) -> Option<(usize, u64)> {
+  fn inspect(&mut self, relief: Relief) -> SolveResult<Option<(usize, u64)>> {
     let Some(item) = self.items.pop_front() else {
-      return None;
+      return Ok(None);
+    };
+
+    let worry = match relief {
+      Relief::Divide => self.operation.evaluate(item).map(|worry| worry / 3),
+      Relief::Modulo(lcm) => self.operation.evaluate_modulo(item, lcm),
     };
+    let worry = worry.map_err(|reason| self.fault(reason, item))?;
+
+    Ok(Some((self.throw(worry % self.test == 0), worry)))
+  }
 
-    let mut worry = match self.operation {
-      Operation::Add(n) => item + n,
-      Operation::Mul(n) => item * n,
-      Operation::Square => item * item,
+  /// Inspects the next item with its exact worry level, as in part 2.
+  fn inspect_exact(&mut self, items: &mut VecDeque<BigUint>) -> SolveResult<Option<(usize, BigUint)>> {
+    let Some(item) = items.pop_front() else {
+      return Ok(None);
     };
 
+    let worry = self.operation.evaluate_exact(&item).map_err(|reason| self.fault(reason, item))?;
 
-    if !PART2 { worry /=  3; }
-    worry %= lcm;
+    Ok(Some((self.throw((&worry % self.test) == BigUint::ZERO), worry)))
+  }
 
+  fn throw(&mut self, divisible: bool) -> usize {
     self.activity += 1;
-    
-    if worry % self.test == 0 {
-      Some((self.if_true, worry))
-    } else {
-      Some((self.if_false, worry))
-    }
+
+    if divisible { self.if_true } else { self.if_false }
+  }
+
+  fn fault(&self, reason: &str, item: impl Display) -> SolveError {
+    SolveError::logic(format!("new = {} {reason} for the worry level {item}", self.operation))
   }
 }
 
-const MONKEY_PATTERN: &str = r"(?P<monkey>Monkey (?P<monkey_id>\d+):\n\s+Starting items: (?P<items>((\d+)(, )?)+)\n\s+Operation: new = old (?P<operation>\*|\+) (?P<rhs>old|\d+)\n\s+Test: divisible by (?P<divisible>\d+)\n\s+If true: throw to monkey (?P<true>\d+)\n\s+If false: throw to monkey (?P<false>\d+))";
-const ITEM_PATTERN: &str = r"((?P<item>\d+)(, )?)";
+/// The lines describing a monkey, each with the text it is expected to hold.
+const MONKEY_LINES: [(&str, &str); 6] = [
+  (r"^Monkey (\d+):$", "Expected Monkey <id>:"),
+  (r"^\s+Starting items: (\d+(?:, \d+)*)$", "Expected Starting items: <item>, <item>, ..."),
+  (r"^\s+Operation: new = (.+)$", "Expected Operation: new = <expression>"),
+  (r"^\s+Test: divisible by (\d+)$", "Expected Test: divisible by <divisor>"),
+  (r"^\s+If true: throw to monkey (\d+)$", "Expected If true: throw to monkey <id>"),
+  (r"^\s+If false: throw to monkey (\d+)$", "Expected If false: throw to monkey <id>"),
+];
 
 lazy_static! {
-  static ref MONKEY_REGEX: Regex = Regex::new(MONKEY_PATTERN).unwrap();
-  static ref ITEM_REGEX: Regex = Regex::new(ITEM_PATTERN).unwrap();
+  static ref MONKEY_REGEXES: Vec<Regex> = MONKEY_LINES.iter().map(|(pattern, _)| Regex::new(pattern).unwrap()).collect();
 }
 
-pub fn solve() -> Result<(String, String), Box<dyn Error>> {
-  let input = read_input_string("day11.txt")?;
+pub struct Day11;
 
-  let mut monkeys = parse_monkeys(&input);
-  
-  let lcm = monkeys.iter().map(|m| m.test).product::<u64>();
+impl Solution for Day11 {
+  type Parsed = Vec<Monkey>;
+
+  const DAY: usize = 11;
+  const TITLE: &'static str = "Monkey in the Middle";
+
+  fn parse(input: &str) -> SolveResult<Self::Parsed> {
+    let monkeys = parse_monkeys(input)?;
+
+    if monkeys.is_empty() {
+      return Err(SolveError::parse(input, "", "Expected the notes on the monkeys"));
+    }
+
+    for (index, monkey) in monkeys.iter().enumerate() {
+      for target in [monkey.if_true, monkey.if_false] {
+        if target >= monkeys.len() {
+          return Err(SolveError::logic(format!("Monkey {index} throws to monkey {target}, which does not exist")));
+        }
+
+        // An item thrown back to its own monkey would be inspected forever.
+        if target == index {
+          return Err(SolveError::logic(format!("Monkey {index} throws to itself")));
+        }
+      }
+    }
+
+    Ok(monkeys)
+  }
+
+  fn part1(monkeys: &Self::Parsed) -> SolveResult<String> {
+    let mut monkeys = monkeys.clone();
+
+    for _ in 0..20 {
+      step_round(&mut monkeys, Relief::Divide)?;
+    }
+
+    monkeys.sort_by(|a, b| b.activity.partial_cmp(&a.activity).unwrap());
+
+    let part1 = monkeys.iter().take(2).map(|monkey| monkey.activity).product::<usize>();
+
+    Ok(part1.to_string())
+  }
+
+  fn part2(monkeys: &Self::Parsed) -> SolveResult<String> {
+    // Worry levels are only kept small by working modulo the tests, which
+    // division does not survive, and which hides a difference below zero.
+    for (operator, verb) in [(Operator::Div, "divides"), (Operator::Sub, "subtracts")] {
+      if let Some(monkey) = monkeys.iter().position(|m| m.operation.uses(operator)) {
+        return Err(SolveError::logic(format!("Monkey {monkey} {verb}, so worry levels cannot be kept small")));
+      }
+    }
 
-  let mut monkeys2 = monkeys.clone();
+    let mut monkeys = monkeys.clone();
 
-  (0..20).for_each(|_| step_round::<false>(&mut monkeys, lcm));
+    let relief = Relief::Modulo(lcm(&monkeys)?);
 
-  monkeys.sort_by(|a, b| b.activity.partial_cmp(&a.activity).unwrap());
+    for _ in 0..10000 {
+      step_round(&mut monkeys, relief)?;
+    }
 
-  let part1 = monkeys.iter().take(2).map(|monkey| monkey.activity).product::<usize>();
+    monkeys.sort_by(|a, b| b.activity.partial_cmp(&a.activity).unwrap());
 
-  (0..10000).for_each(|_| step_round::<true>(&mut monkeys2, lcm));
+    let part2 = monkeys.iter().take(2).map(|m| m.activity).product::<usize>();
 
-  monkeys2.sort_by(|a, b| b.activity.partial_cmp(&a.activity).unwrap());
+    Ok(part2.to_string())
+  }
+}
 
-  let part2 = monkeys2.iter().take(2).map(|m| m.activity).product::<usize>();
+/// The least common multiple of the divisors the monkeys test with, modulo
+/// which part 2 keeps the worry levels.
+pub fn lcm(monkeys: &[Monkey]) -> SolveResult<u64> {
+  fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+  }
 
-  Ok((part1.to_string(), part2.to_string()))
+  monkeys
+    .iter()
+    .try_fold(1, |lcm: u64, monkey| (lcm / gcd(lcm, monkey.test)).checked_mul(monkey.test))
+    .ok_or_else(|| SolveError::logic("The least common multiple of the divisors overflows"))
 }
 
-fn step_round<const PART2: bool>(monkeys: &mut Vec<Monkey>, lcm: u64) {
+pub fn step_round(monkeys: &mut [Monkey], relief: Relief) -> SolveResult<()> {
+  step_round_traced(monkeys, relief, |_, _| ())
+}
+
+/// Like [`step_round`], calling `on_throw` with the monkey throwing and the
+/// monkey catching each item.
+pub fn step_round_traced(monkeys: &mut [Monkey], relief: Relief, mut on_throw: impl FnMut(usize, usize)) -> SolveResult<()> {
   for from in 0..monkeys.len() {
-    while let Some((to, item)) = monkeys[from].inspect::<PART2>(lcm) {
+    while let Some((to, item)) = monkeys[from].inspect(relief)? {
+      on_throw(from, to);
       monkeys[to].items.push_back(item);
     }
   }
+
+  Ok(())
 }
 
-fn parse_monkeys(input: &String) -> Vec<Monkey> {
-  MONKEY_REGEX.captures_iter(input).map(|caps| { 
-    let items = ITEM_REGEX
-      .captures_iter(caps.name("items").unwrap().as_str())
-      .map(|m| {
-        m.get(2).unwrap().as_str().parse().unwrap()
-      })
-      .collect();
-
-    let rhs = caps.name("rhs").unwrap().as_str();
-
-    Monkey {
-      items,
-      operation: match caps.name("operation").unwrap().as_str() {
-        "*" => match rhs {
-          "old" => Operation::Square,
-          n => Operation::Mul(n.parse().unwrap()),
-        },
-        "+" => Operation::Add(rhs.parse().unwrap()),
-        _ => unreachable!(),
-      },
-      activity: 0,
-      test: caps.name("divisible").unwrap().as_str().parse().unwrap(),
-      if_true: caps.name("true").unwrap().as_str().parse().unwrap(),
-      if_false: caps.name("false").unwrap().as_str().parse().unwrap(),
+/// Every throw of `rounds` rounds of part 2, as the monkey throwing and the
+/// monkey catching, with worry levels kept modulo the tests.
+pub fn throws(monkeys: &[Monkey], rounds: usize) -> SolveResult<Vec<(usize, usize)>> {
+  let (mut monkeys, relief) = (monkeys.to_vec(), Relief::Modulo(lcm(monkeys)?));
+  let mut throws = Vec::new();
+
+  for _ in 0..rounds {
+    step_round_traced(&mut monkeys, relief, |from, to| throws.push((from, to)))?;
+  }
+
+  Ok(throws)
+}
+
+/// Like [`throws`], with exact worry levels. They grow quickly, so this is
+/// only practical for a few rounds, to check the modular arithmetic against.
+pub fn exact_throws(monkeys: &[Monkey], rounds: usize) -> SolveResult<Vec<(usize, usize)>> {
+  let mut monkeys = monkeys.to_vec();
+  let mut items = monkeys
+    .iter_mut()
+    .map(|monkey| monkey.items.drain(..).map(BigUint::from).collect::<VecDeque<_>>())
+    .collect::<Vec<_>>();
+  let mut throws = Vec::new();
+
+  for _ in 0..rounds {
+    for from in 0..monkeys.len() {
+      while let Some((to, item)) = monkeys[from].inspect_exact(&mut items[from])? {
+        throws.push((from, to));
+        items[to].push_back(item);
+      }
+    }
+  }
+
+  Ok(throws)
+}
+
+/// Parses the right-hand side of an operation, `text`, a slice of `source`.
+/// The operands are `old` and integers, and the operators `+ - * /`.
+pub fn parse_expression(source: &str, text: &str) -> SolveResult<Expression> {
+  let tokens = tokenize(source, text)?;
+  let mut tokens = tokens.iter().copied().peekable();
+
+  let expression = parse_binary(source, text, &mut tokens, 1)?;
+
+  match tokens.next() {
+    None => Ok(expression),
+    Some(token) => Err(SolveError::parse(source, token, "Expected an operator")),
+  }
+}
+
+/// Splits `text` into `old`, numbers and operators, skipping whitespace.
+fn tokenize<'a>(source: &str, text: &'a str) -> SolveResult<Vec<&'a str>> {
+  let mut tokens = Vec::new();
+  let mut rest = text.trim_start();
+
+  while let Some(c) = rest.chars().next() {
+    let length = if c.is_ascii_digit() {
+      rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len())
+    } else if rest.starts_with("old") {
+      3
+    } else if "+-*/".contains(c) {
+      1
+    } else {
+      return Err(SolveError::parse(source, &rest[..c.len_utf8()], "Expected old, a number or an operator"));
+    };
+
+    tokens.push(&rest[..length]);
+    rest = rest[length..].trim_start();
+  }
+
+  Ok(tokens)
+}
+
+/// Parses operands joined by operators of at least `precedence`, grouping
+/// them from the left.
+fn parse_binary<'a>(
+  source: &str,
+  text: &'a str,
+  tokens: &mut Peekable<impl Iterator<Item = &'a str>>,
+  precedence: u8,
+) -> SolveResult<Expression> {
+  let mut left = parse_operand(source, text, tokens)?;
+
+  while let Some(operator) = tokens.peek().and_then(|token| Operator::from_token(token)) {
+    if operator.precedence() < precedence {
+      break;
     }
-  }).collect()
+
+    tokens.next();
+    let right = parse_binary(source, text, tokens, operator.precedence() + 1)?;
+    left = Expression::Binary(Box::new(left), operator, Box::new(right));
+  }
+
+  Ok(left)
+}
+
+fn parse_operand<'a>(source: &str, text: &'a str, tokens: &mut impl Iterator<Item = &'a str>) -> SolveResult<Expression> {
+  match tokens.next() {
+    Some("old") => Ok(Expression::Old),
+    Some(token) if token.starts_with(|c: char| c.is_ascii_digit()) => Ok(Expression::Literal(parse_number(source, token)?)),
+    Some(token) => Err(SolveError::parse(source, token, "Expected old or a number")),
+    None => Err(SolveError::parse(source, &text[text.len()..], "Expected old or a number")),
+  }
+}
+
+pub fn parse_monkeys(input: &str) -> SolveResult<Vec<Monkey>> {
+  input
+    .split("\n\n")
+    .filter(|block| !block.trim().is_empty())
+    .enumerate()
+    .map(|(index, block)| parse_monkey(input, block, index))
+    .collect()
+}
+
+/// Parses the notes on the monkey at `index`, a block of `input`.
+fn parse_monkey(input: &str, block: &str, index: usize) -> SolveResult<Monkey> {
+  let block = block.trim_end();
+  let mut lines = block.lines();
+
+  let fields = MONKEY_LINES
+    .iter()
+    .zip(MONKEY_REGEXES.iter())
+    .map(|((_, expected), regex)| {
+      let line = lines.next().unwrap_or(&block[block.len()..]);
+
+      regex
+        .captures(line)
+        .map(|caps| caps.get(1).unwrap().as_str())
+        .ok_or_else(|| SolveError::parse(input, line, *expected))
+    })
+    .collect::<SolveResult<Vec<_>>>()?;
+
+  if let Some(line) = lines.next() {
+    return Err(SolveError::parse(input, line, "Expected a blank line before the next monkey"));
+  }
+
+  let [monkey_id, items, operation, test, if_true, if_false] = fields[..] else {
+    unreachable!("every line of the notes has a field");
+  };
+
+  if parse_number::<usize>(input, monkey_id)? != index {
+    return Err(SolveError::parse(input, monkey_id, format!("Expected monkey {index}")));
+  }
+
+  let items = items
+    .split(", ")
+    .map(|item| parse_number(input, item))
+    .collect::<SolveResult<_>>()?;
+
+  let divisor = test;
+  let test = parse_number(input, divisor)?;
+  if test == 0 {
+    return Err(SolveError::parse(input, divisor, "Expected a divisor other than 0"));
+  }
+
+  Ok(Monkey {
+    items,
+    operation: parse_expression(input, operation)?,
+    activity: 0,
+    test,
+    if_true: parse_number(input, if_true)?,
+    if_false: parse_number(input, if_false)?,
+  })
 }
 
 #[cfg(test)]
 mod tests {
   use std::collections::VecDeque;
 
-  use super::{parse_monkeys, Operation, Monkey, step_round};
+  use super::{exact_throws, lcm, parse_expression, parse_monkeys, step_round, throws, Day11, Expression, Monkey, Relief};
+  use crate::solution::Solution;
+
+  const SAMPLE: &str = "\
+Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 2
+    If false: throw to monkey 3
+
+Monkey 1:
+  Starting items: 54, 65, 75, 74
+  Operation: new = old + 6
+  Test: divisible by 19
+    If true: throw to monkey 2
+    If false: throw to monkey 0
+
+Monkey 2:
+  Starting items: 79, 60, 97
+  Operation: new = old * old
+  Test: divisible by 13
+    If true: throw to monkey 1
+    If false: throw to monkey 3
+
+Monkey 3:
+  Starting items: 74
+  Operation: new = old + 3
+  Test: divisible by 17
+    If true: throw to monkey 0
+    If false: throw to monkey 1";
+
+  fn operation(text: &str) -> Expression {
+    parse_expression(text, text).unwrap()
+  }
 
   #[test]
   fn test_part2() {
     let mut input = vec![
       Monkey {
         items: VecDeque::from(vec![79, 98]),
-        operation: Operation::Mul(19),
+        operation: operation("old * 19"),
         test: 23,
         if_true: 2,
         if_false: 3,
@@ -137,7 +532,7 @@ mod tests {
       },
       Monkey {
         items: VecDeque::from(vec![54, 65, 75, 74]),
-        operation: Operation::Add(6),
+        operation: operation("old + 6"),
         test: 19,
         if_true: 2,
         if_false: 0,
@@ -145,7 +540,7 @@ mod tests {
       },
       Monkey {
         items: VecDeque::from(vec![79, 60, 97]),
-        operation: Operation::Square,
+        operation: operation("old * old"),
         test: 13,
         if_true: 1,
         if_false: 3,
@@ -153,7 +548,7 @@ mod tests {
       },
       Monkey {
         items: VecDeque::from(vec![74]),
-        operation: Operation::Add(3),
+        operation: operation("old + 3"),
         test: 17,
         if_true: 0,
         if_false: 1,
@@ -161,18 +556,18 @@ mod tests {
       },
     ];
 
-    let lcm = input.iter().map(|m| m.test).product::<u64>();
+    let lcm = lcm(&input).unwrap();
 
-    (0..20).for_each(|_| step_round::<true>(&mut input, lcm));
+    (0..20).for_each(|_| step_round(&mut input, Relief::Modulo(lcm)).unwrap());
     assert_eq!(input.iter().map(|m| m.activity).collect::<Vec<_>>(), vec![99, 97, 8, 103]);
     
-    (20..1000).for_each(|_| step_round::<true>(&mut input, lcm));
+    (20..1000).for_each(|_| step_round(&mut input, Relief::Modulo(lcm)).unwrap());
     assert_eq!(input.iter().map(|m| m.activity).collect::<Vec<_>>(), vec![5204, 4792, 199, 5192]);
 
-    (1000..2000).for_each(|_| step_round::<true>(&mut input, lcm));
+    (1000..2000).for_each(|_| step_round(&mut input, Relief::Modulo(lcm)).unwrap());
     assert_eq!(input.iter().map(|m| m.activity).collect::<Vec<_>>(), vec![10419, 9577, 392, 10391]);
 
-    (2000..10000).for_each(|_| step_round::<true>(&mut input, lcm));
+    (2000..10000).for_each(|_| step_round(&mut input, Relief::Modulo(lcm)).unwrap());
     assert_eq!(input.iter().map(|m| m.activity).collect::<Vec<_>>(), vec![52166, 47830, 1938, 52013]);
 
     input.sort_by(|a, b| b.activity.partial_cmp(&a.activity).unwrap());
@@ -185,7 +580,7 @@ mod tests {
     let mut input = vec![
       Monkey {
         items: VecDeque::from(vec![79, 98]),
-        operation: Operation::Mul(19),
+        operation: operation("old * 19"),
         test: 23,
         if_true: 2,
         if_false: 3,
@@ -193,7 +588,7 @@ mod tests {
       },
       Monkey {
         items: VecDeque::from(vec![54, 65, 75, 74]),
-        operation: Operation::Add(6),
+        operation: operation("old + 6"),
         test: 19,
         if_true: 2,
         if_false: 0,
@@ -201,7 +596,7 @@ mod tests {
       },
       Monkey {
         items: VecDeque::from(vec![79, 60, 97]),
-        operation: Operation::Square,
+        operation: operation("old * old"),
         test: 13,
         if_true: 1,
         if_false: 3,
@@ -209,7 +604,7 @@ mod tests {
       },
       Monkey {
         items: VecDeque::from(vec![74]),
-        operation: Operation::Add(3),
+        operation: operation("old + 3"),
         test: 17,
         if_true: 0,
         if_false: 1,
@@ -217,8 +612,6 @@ mod tests {
       },
     ];
 
-    let lcm = input.iter().map(|m| m.test).product::<u64>();
-    
     let expected = vec![
       vec![
         vec![20, 23, 27, 26],
@@ -295,32 +688,164 @@ mod tests {
     ];
 
     (0..10).for_each(|i| {
-      step_round::<false>(&mut input, lcm);
+      step_round(&mut input, Relief::Divide).unwrap();
       let output = input.iter().map(|m| m.items.clone()).collect::<Vec<_>>();
       assert_eq!(output, expected[i]);
     });
 
-    (10..15).for_each(|_| step_round::<false>(&mut input, lcm));
+    (10..15).for_each(|_| step_round(&mut input, Relief::Divide).unwrap());
     let output = input.iter().map(|m| m.items.clone()).collect::<Vec<_>>();
     assert_eq!(output, expected[10]);
 
-    (15..20).for_each(|_| step_round::<false>(&mut input, lcm));
+    (15..20).for_each(|_| step_round(&mut input, Relief::Divide).unwrap());
     let output = input.iter().map(|m| m.items.clone()).collect::<Vec<_>>();
     assert_eq!(output, expected[11]);
   }
   
   #[test]
   fn test_parse_monkeys() {
-    let input = String::from("Monkey 1:\n    Starting items: 95, 88, 75, 81, 91, 67, 65, 84\n    Operation: new = old * 11\n    Test: divisible by 7\n      If true: throw to monkey 3\n      If false: throw to monkey 4");
-    let output = parse_monkeys(&input);
+    let input = String::from("Monkey 0:\n    Starting items: 95, 88, 75, 81, 91, 67, 65, 84\n    Operation: new = old * 11\n    Test: divisible by 7\n      If true: throw to monkey 3\n      If false: throw to monkey 4");
+    let output = parse_monkeys(&input).unwrap();
     let expected = Monkey {
       items: VecDeque::from(vec![95, 88, 75, 81, 91, 67, 65, 84]),
-      operation: Operation::Mul(11),
+      operation: operation("old * 11"),
       test: 7,
       if_true: 3,
       if_false: 4,
       activity: 0,
     };
     assert_eq!(output[0], expected);
+
+    let error = parse_monkeys(&input.replace("by 7", "by 0")).unwrap_err();
+    assert_eq!(error.to_string(), "<input>:4:24: Expected a divisor other than 0, found \"0\"");
+
+    for (from, to, error) in [
+      ("95, 88", "54, x5", "<input>:2:1: Expected Starting items: <item>, <item>, ..., found \"    Starting items: 54, x5, 75, 81, 91, 67, 65, 84\""),
+      ("Monkey 0", "Monkey 1", "<input>:1:8: Expected monkey 0, found \"1\""),
+      ("Test:", "Tset:", "<input>:4:1: Expected Test: divisible by <divisor>, found \"    Tset: divisible by 7\""),
+      ("\n      If false: throw to monkey 4", "", "<input>:5:33: Expected If false: throw to monkey <id>"),
+      ("monkey 4", "monkey 4\n    Extra: note", "<input>:7:1: Expected a blank line before the next monkey, found \"    Extra: note\""),
+    ] {
+      assert_eq!(parse_monkeys(&input.replace(from, to)).unwrap_err().to_string(), error, "{to}");
+    }
+
+    let monkeys = parse_monkeys(&format!("{input}\n\n{}\n", input.replace("Monkey 0", "Monkey 1"))).unwrap();
+    assert_eq!(monkeys.len(), 2);
   }
-}
\ No newline at end of file
+
+  #[test]
+  fn test_parse_expression() {
+    for (text, old, expected) in [
+      ("old * old", 7, Ok(49)),
+      ("old + old", 7, Ok(14)),
+      ("3 * old", 7, Ok(21)),
+      ("old - 2", 7, Ok(5)),
+      ("old / 2", 7, Ok(3)),
+      ("2 + old * 3", 4, Ok(14)),
+      ("old - 1 - 1", 5, Ok(3)),
+      ("old*2-old/2", 10, Ok(15)),
+      ("old - 8", 7, Err("goes below zero")),
+      ("old / 0", 7, Err("divides by zero")),
+    ] {
+      assert_eq!(operation(text).evaluate(old), expected, "{text}");
+    }
+
+    assert_eq!(operation("old*2-old/2").to_string(), "old * 2 - old / 2");
+    assert_eq!(operation("old - 1 - 1"), Expression::Binary(Box::new(operation("old - 1")), super::Operator::Sub, Box::new(Expression::Literal(1))));
+
+    for (text, error) in [
+      ("old +", "<input>:1:6: Expected old or a number"),
+      ("old ^ 2", "<input>:1:5: Expected old, a number or an operator, found \"^\""),
+      ("old 2", "<input>:1:5: Expected an operator, found \"2\""),
+      ("* old", "<input>:1:1: Expected old or a number, found \"*\""),
+      ("99999999999999999999", "<input>:1:1: Expected a number, found \"99999999999999999999\""),
+    ] {
+      assert_eq!(parse_expression(text, text).unwrap_err().to_string(), error, "{text}");
+    }
+  }
+
+  #[test]
+  fn test_evaluate_modulo() {
+    let modulus = 23 * 19;
+
+    for text in ["old * old", "old + 6", "2 * old + 400", "old * 19 + old"] {
+      for old in [0, 7, 436, 10_000] {
+        let exact = operation(text).evaluate(old).map(|worry| worry % modulus);
+        if let Ok(exact) = exact {
+          assert_eq!(operation(text).evaluate_modulo(old % modulus, modulus), Ok(exact), "{text} at {old}");
+        }
+      }
+    }
+
+    assert_eq!(operation("old / 2").evaluate_modulo(4, modulus), Err("cannot divide modulo the tests"));
+    assert_eq!(operation("old - 5").evaluate_modulo(7, modulus), Err("cannot subtract modulo the tests"));
+  }
+
+  #[test]
+  fn test_custom_operations() {
+    let notes = "Monkey 0:\n  Starting items: 10\n  Operation: new = old * 4 / 2\n  Test: divisible by 2\n    If true: throw to monkey 1\n    If false: throw to monkey 1\n\n\
+      Monkey 1:\n  Starting items: 1\n  Operation: new = old + old\n  Test: divisible by 3\n    If true: throw to monkey 0\n    If false: throw to monkey 0";
+    let monkeys = Day11::parse(notes).unwrap();
+    assert_eq!(monkeys[0].operation.to_string(), "old * 4 / 2");
+    assert!(Day11::part1(&monkeys).is_ok());
+    assert_eq!(Day11::part2(&monkeys).unwrap_err().to_string(), "Monkey 0 divides, so worry levels cannot be kept small");
+
+    let error = Day11::parse(&notes.replace("If false: throw to monkey 1", "If false: throw to monkey 0")).unwrap_err();
+    assert_eq!(error.to_string(), "Monkey 0 throws to itself");
+    let error = Day11::parse(&notes.replace("If true: throw to monkey 1", "If true: throw to monkey 2")).unwrap_err();
+    assert_eq!(error.to_string(), "Monkey 0 throws to monkey 2, which does not exist");
+
+    // Worry levels below zero are reported rather than wrapped around.
+    let monkeys = Day11::parse(&notes.replace("old * 4 / 2", "old - 20")).unwrap();
+    assert_eq!(Day11::part1(&monkeys).unwrap_err().to_string(), "new = old - 20 goes below zero for the worry level 10");
+
+    // Part 2 cannot tell whether a difference went below zero, so it refuses
+    // to subtract rather than answer for input part 1 rejects.
+    let below_zero = notes.replace("old * 4 / 2", "old - 5").replace("items: 10", "items: 1").replace("old + old", "old + 1");
+    let monkeys = Day11::parse(&below_zero).unwrap();
+    assert_eq!(Day11::part1(&monkeys).unwrap_err().to_string(), "new = old - 5 goes below zero for the worry level 1");
+    assert_eq!(Day11::part2(&monkeys).unwrap_err().to_string(), "Monkey 0 subtracts, so worry levels cannot be kept small");
+
+    let error = parse_monkeys(&notes.replace("old + old", "old % 2")).unwrap_err();
+    assert_eq!(error.to_string(), "<input>:10:24: Expected old, a number or an operator, found \"%\"");
+  }
+
+  #[test]
+  fn test_overflow() {
+    let notes = "Monkey 0:\n  Starting items: 4294967296\n  Operation: new = old * old\n  Test: divisible by 2\n    If true: throw to monkey 1\n    If false: throw to monkey 1\n\n\
+      Monkey 1:\n  Starting items: 1\n  Operation: new = old\n  Test: divisible by 3\n    If true: throw to monkey 0\n    If false: throw to monkey 0";
+    let monkeys = Day11::parse(notes).unwrap();
+    assert_eq!(Day11::part1(&monkeys).unwrap_err().to_string(), "new = old * old overflows for the worry level 4294967296");
+    assert!(Day11::part2(&monkeys).is_ok());
+
+    // Modulo divisors whose product fits but whose square does not, the
+    // product of two levels overflows too.
+    let large = Day11::parse(&notes.replace("by 2", "by 1099511627791")).unwrap();
+    assert_eq!(lcm(&large).unwrap(), 3 * 1099511627791);
+    assert_eq!(Day11::part2(&large).unwrap_err().to_string(), "new = old * old overflows for the worry level 4294967296");
+
+    let monkeys = Day11::parse(&notes.replace("by 2", "by 18446744073709551557")).unwrap();
+    assert_eq!(lcm(&monkeys).unwrap_err().to_string(), "The least common multiple of the divisors overflows");
+    assert_eq!(lcm(&Day11::parse(&notes.replace("by 3", "by 4")).unwrap()).unwrap(), 4);
+  }
+
+  #[test]
+  fn test_throws() {
+    let monkeys = Day11::parse(SAMPLE).unwrap();
+    let throws = throws(&monkeys, 1).unwrap();
+    assert_eq!(throws.len(), 2 + 4 + 3 + 6);
+    assert_eq!(throws[..3], [(0, 3), (0, 3), (1, 0)]);
+  }
+
+  /// The modular arithmetic of part 2 routes every item as exact worry levels
+  /// would.
+  #[test]
+  fn test_exact_throws() {
+    let monkeys = Day11::parse(SAMPLE).unwrap();
+    assert_eq!(exact_throws(&monkeys, 20).unwrap(), throws(&monkeys, 20).unwrap());
+
+    let custom = SAMPLE.replace("old * 19", "old * 19 + old + 7").replace("old + 3", "3 * old + old");
+    let monkeys = Day11::parse(&custom).unwrap();
+    assert_eq!(exact_throws(&monkeys, 20).unwrap(), throws(&monkeys, 20).unwrap());
+  }
+}
@@ -1,352 +1,297 @@
-use std::{error::Error, collections::VecDeque};
+use std::cmp::Ordering;
+use std::collections::HashMap;
 
-use crate::utils::read_input_string;
+use crate::output::Style;
+use crate::solution::{Parts, Solution, SolveResult};
+use crate::utils::grid::Grid;
+use crate::utils::search::{bfs, Search};
+use crate::utils::SolveError;
 
+/// A heightmap, with the start and the end replaced by their elevations `a`
+/// and `z`.
 #[derive(Debug, PartialEq, Clone)]
-struct Graph {
-  width: usize,
-  height: usize,
-  vertices: Vec<u8>,
-  edges: Vec<(bool, bool, bool, bool)>,
-  start: usize,
-  end: usize,
+pub struct Graph {
+  pub heights: Grid<u8>,
+  pub start: (usize, usize),
+  pub end: (usize, usize),
 }
 
-pub fn solve() -> Result<(String, String), Box<dyn Error>> {
-  let input = read_input_string("day12.txt")?;
+impl Graph {
+  /// The squares one step away from `position` that are at most one higher.
+  pub fn edges(&self, position: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let height = self.heights[position];
+    self.heights.neighbours4(position).filter(move |&next| self.heights[next] <= height + 1)
+  }
 
-  let graph = create_graph(input);
+  /// The squares one step away from which `position` can be reached, the
+  /// edges of [`Graph::edges`] walked backwards.
+  pub fn reverse_edges(&self, position: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let height = self.heights[position];
+    self.heights.neighbours4(position).filter(move |&previous| height <= self.heights[previous] + 1)
+  }
 
-  let size = graph.width * graph.height;
+  /// Searches backwards from the end once, finding the fewest steps to the
+  /// end from every square.
+  pub fn distances(&self) -> Distances<'_> {
+    Distances { graph: self, search: bfs([self.end], |&position| self.reverse_edges(position), |_| false) }
+  }
 
-  let mut queue = VecDeque::<usize>::with_capacity(size);
-  queue.push_back(graph.start);
+  /// Searches for the fewest steps from the nearest of `starts` to the end.
+  pub fn climb(&self, starts: impl IntoIterator<Item = (usize, usize)>) -> Search<(usize, usize), usize> {
+    bfs(starts, |&position| self.edges(position), |&position| position == self.end)
+  }
 
-  let mut distance = vec![i32::MAX; size];
-  distance[graph.start] = 0;
+  /// The squares at the lowest elevation `a`, where part 2 may start.
+  pub fn lowest(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+    self.heights.iter().filter(|(_, &height)| height == b'a').map(|(position, _)| position)
+  }
 
-  let part1 = dijkstra2(&graph, &mut queue, &mut distance);
+  /// The squares of a shortest path from the nearest of `starts` to the end,
+  /// both included.
+  pub fn shortest_path(&self, starts: impl IntoIterator<Item = (usize, usize)>) -> SolveResult<Vec<(usize, usize)>> {
+    self.climb(starts).goal_path().ok_or_else(unreachable)
+  }
+}
 
-  queue.clear();
-  
-  let mut distance = vec![i32::MAX; size];
+/// The fewest steps from every square of a heightmap to its end, to answer
+/// many queries against the same map.
+#[derive(Debug, Clone)]
+pub struct Distances<'a> {
+  graph: &'a Graph,
+  search: Search<(usize, usize), usize>,
+}
 
-  graph.vertices
-    .iter()
-    .enumerate()
-    .filter(|(_, c)| **c == b'a')
-    .for_each(|(start, _)| {
-      queue.push_back(start);
-      distance[start] = 0;
-    });
+impl Distances<'_> {
+  /// The fewest steps from `position` to the end, `None` when the end cannot
+  /// be reached from it.
+  pub fn to_end(&self, position: (usize, usize)) -> Option<usize> {
+    self.search.cost(&position)
+  }
 
-  let part2 = dijkstra2(&graph, &mut queue, &mut distance);
+  /// The square closest to the end among those matching `predicate`, which is
+  /// given each position and its elevation, with its number of steps. Ties
+  /// go to the first square row by row.
+  pub fn nearest(&self, mut predicate: impl FnMut((usize, usize), u8) -> bool) -> Option<((usize, usize), usize)> {
+    self
+      .graph
+      .heights
+      .iter()
+      .filter(|&(position, &height)| predicate(position, height))
+      .filter_map(|(position, _)| Some((position, self.to_end(position)?)))
+      .min_by_key(|&(_, steps)| steps)
+  }
 
-  Ok((part1.to_string(), part2.to_string()))
+  /// The squares of a shortest path from `position` to the end, both included.
+  pub fn path(&self, position: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+    let mut path = self.search.path(&position)?;
+    path.reverse();
+    Some(path)
+  }
 }
 
-fn dijkstra2(graph: &Graph, queue: &mut VecDeque<usize>, distance: &mut Vec<i32>) -> i32 {
-  while !queue.is_empty() {
-    let u = queue.pop_front().unwrap();
-    
-    let (top, right, bottom, left) = graph.edges[u];
-
-    if top {
-      let v = u - graph.width;
-      let alt = distance[u] + 1;
-      if alt < distance[v] {
-        distance[v] = alt;
-        queue.push_back(v);
-      }
-    }
+pub struct Day12;
 
-    if right {
-      let v = u + 1;
-      let alt = distance[u] + 1;
-      if alt < distance[v] {
-        distance[v] = alt;
-        queue.push_back(v);
-      }
-    }
+impl Solution for Day12 {
+  type Parsed = Graph;
 
-    if bottom {
-      let v = u + graph.width;
-      let alt = distance[u] + 1;
-      if alt < distance[v] {
-        distance[v] = alt;
-        queue.push_back(v);
-      }
-    }
+  const DAY: usize = 12;
+  const TITLE: &'static str = "Hill Climbing Algorithm";
 
-    if left {
-      let v = u - 1;
-      let alt = distance[u] + 1;
-      if alt < distance[v] {
-        distance[v] = alt;
-        queue.push_back(v);
-      }
-    }
+  fn parse(input: &str) -> SolveResult<Self::Parsed> {
+    create_graph(input)
   }
 
-  distance[graph.end]
-}
-
-#[allow(dead_code)]
-fn dijkstra<const PRINT: bool>(graph: &Graph) -> i32 {
-  let size = graph.width * graph.height;
-
-  let mut queue = VecDeque::<usize>::with_capacity(size);
-  queue.push_back(graph.start);
+  fn part1(graph: &Self::Parsed) -> SolveResult<String> {
+    steps_to_end(&graph.climb([graph.start]))
+  }
 
-  let mut distance = vec![i32::MAX; size];
-  distance[graph.start] = 0;
+  fn part2(graph: &Self::Parsed) -> SolveResult<String> {
+    let (_, steps) = graph.distances().nearest(|_, height| height == b'a').ok_or_else(unreachable)?;
+    Ok(steps.to_string())
+  }
 
-  let mut previous = vec![None; size];
-  
-  while !queue.is_empty() {
-    let u = queue.pop_front().unwrap();
+  /// Lists the squares of the path of each part and draws it over the
+  /// heightmap. The path of part 2 starts at the `a` that is the closest.
+  fn render(graph: &Self::Parsed, parts: Parts, style: Style) -> SolveResult<Option<String>> {
+    let mut output = String::new();
 
-    let (top, right, bottom, left) = graph.edges[u];
+    let distances = graph.distances();
+    let lowest = distances.nearest(|_, height| height == b'a').ok_or_else(unreachable)?;
 
-    if top {
-      let v = u - graph.width;
-      let alt = distance[u] + 1;
-      if alt < distance[v] {
-        distance[v] = alt;
-        previous[v] = Some(u);
-        queue.push_back(v);
+    for (part, solve, start) in [(1, parts.part1(), graph.start), (2, parts.part2(), lowest.0)] {
+      if !solve {
+        continue;
       }
-    }
 
-    if right {
-      let v = u + 1;
-      let alt = distance[u] + 1;
-      if alt < distance[v] {
-        distance[v] = alt;
-        previous[v] = Some(u);
-        queue.push_back(v);
-      }
-    }
+      let path = distances.path(start).ok_or_else(unreachable)?;
+      let squares = path.iter().map(|(x, y)| format!("({x}, {y})")).collect::<Vec<_>>().join(" ");
 
-    if bottom {
-      let v = u + graph.width;
-      let alt = distance[u] + 1;
-      if alt < distance[v] {
-        distance[v] = alt;
-        previous[v] = Some(u);
-        queue.push_back(v);
-      }
+      output += &format!("Part {part}: {} steps from {:?} to {:?}\n", path.len() - 1, path[0], graph.end);
+      output += &format!("Path: {squares}\n");
+      output += &format!("{}\n", draw_path(graph, &path, style));
     }
 
-    if left {
-      let v = u - 1;
-      let alt = distance[u] + 1;
-      if alt < distance[v] {
-        distance[v] = alt;
-        previous[v] = Some(u);
-        queue.push_back(v);
-      }
-    }
+    Ok(Some(output))
   }
+}
 
-  if PRINT {
-    print_path(&previous, &graph);
-  }
+fn steps_to_end(search: &Search<(usize, usize), usize>) -> SolveResult<String> {
+  search.goal_cost().map(|steps| steps.to_string()).ok_or_else(unreachable)
+}
 
-  distance[graph.end]
+fn unreachable() -> SolveError {
+  SolveError::logic("The end E cannot be reached")
 }
 
-fn print_path(previous: &Vec<Option<usize>>, graph: &Graph) {
-  let mut output = vec![b'.'; graph.width * graph.height];
-  
-  output[graph.end] = b'E';
-  
-  let mut current = graph.end;
-  while let Some(index) = previous[current] {
-    output[index] = b'#';
-
-    if index == current + graph.width{
-      output[index] = b'^';
-    } else if index as i32 == current as i32 - 1 {
-      output[index] = b'>';
-    } else if index as i32 == current as i32 - graph.width as i32 {
-      output[index] = b'V';
-    } else if index == current + 1 {
-      output[index] = b'<';
-    }
+/// Draws the heightmap with each square of `path` replaced by an arrow to the
+/// next one, and its first and last squares by `S` and `E`. With colours the
+/// path is highlighted and the rest of the map dimmed.
+pub fn draw_path(graph: &Graph, path: &[(usize, usize)], style: Style) -> String {
+  let mut arrows = path
+    .windows(2)
+    .map(|step| {
+      let ((x, y), (next_x, next_y)) = (step[0], step[1]);
+      let arrow = match (next_x.cmp(&x), next_y.cmp(&y)) {
+        (Ordering::Greater, _) => '>',
+        (Ordering::Less, _) => '<',
+        (_, Ordering::Greater) => 'v',
+        _ => '^',
+      };
+
+      (step[0], style.paint("1;93", arrow))
+    })
+    .collect::<HashMap<_, _>>();
 
-    current = index;
+  if let (Some(&first), Some(&last)) = (path.first(), path.last()) {
+    arrows.insert(first, style.paint("1;92", 'S'));
+    arrows.insert(last, style.paint("1;91", 'E'));
   }
-  
-  output[current] = b'S';
 
-  (0..=graph.height).for_each(|i| output.insert(i * (graph.width + 1), b'\n'));
+  let mut drawing = String::new();
 
-
-  println!("{}", String::from_utf8(output).unwrap());
-}
-
-fn create_graph(input: String) -> Graph {
-  let width = input.find('\n').unwrap();
-
-  let mut vertices = input
-    .into_bytes()
-    .into_iter()
-    .filter(|c| *c != b'\n')
-    .collect::<Vec<_>>();
-
-  let height = vertices.len() / width;
-  let size = height * width;
-
-  let mut edges = Vec::with_capacity(size);
-  let mut start = 0;
-  let mut end = 0; 
-
-  for i in 0..size {
-    let current = vertices[i];
-    if current == b'S' {
-      start = i; 
-      vertices[i] = b'a';
-    } else if current == b'E' {
-      end = i;
-      vertices[i] = b'z';
+  for (position, &height) in graph.heights.iter() {
+    if position.0 == 0 && position.1 > 0 {
+      drawing.push('\n');
     }
 
-    edges.push(get_edges(&vertices, width, height, i));
+    match arrows.get(&position) {
+      Some(arrow) => drawing.push_str(arrow),
+      None => drawing.push_str(&style.paint("2", height as char)),
+    }
   }
 
-  edges[end - width] = get_edges(&vertices, width, height, end - width);
-  edges[end - 1] = get_edges(&vertices, width, height, end - 1);
-
-  Graph {
-    width,
-    height,
-    vertices,
-    edges,
-    start,
-    end,
-  }
+  drawing
 }
 
-fn get_edges(vertices: &Vec<u8>, width: usize, height: usize, index: usize) -> (bool, bool, bool, bool) {
-  let mut ret = (false, false, false, false);
+pub fn create_graph(input: &str) -> SolveResult<Graph> {
+  let mut heights = Grid::parse(input, |c| match c {
+    'a'..='z' | 'S' | 'E' => Ok(c as u8),
+    _ => Err(String::from("Expected an elevation a-z, S or E")),
+  })?;
 
-  let vertex = vertices[index];
+  let (Some(start), Some(end)) = (heights.find(|&c| c == b'S'), heights.find(|&c| c == b'E')) else {
+    return Err(SolveError::parse(input, &input[input.len()..], "Expected a heightmap with a start S and an end E"));
+  };
 
-  if index >= width {
-    let top = vertices[index - width];
+  heights[start] = b'a';
+  heights[end] = b'z';
 
-    if vertex > top - 2 {
-      ret.0 = true;
-    }
-  }
+  Ok(Graph { heights, start, end })
+}
 
-  if index % width < width - 1 {
-    let right = vertices[index + 1];
-    
-    if vertex > right - 2 {
-      ret.1 = true;
-    }
-  }
+#[cfg(test)]
+mod tests {
+  use super::{create_graph, draw_path, Day12};
+  use crate::output::Style;
+  use crate::solution::{Parts, Solution};
 
-  if index / width < height - 1 {
-    let bottom = vertices[index + width];
-    if vertex > bottom - 2 {
-      ret.2 = true;
-    }
-  }
+  const HEIGHTMAP: &str = "Sabqponm\nabcryxxl\naccszExk\nacctuvwj\nabdefghi";
 
-  if index % width > 0 {
-    let left = vertices[index - 1];
-    
-    if vertex > left - 2 {
-      ret.3 = true;
-    }
-  }
+  #[test]
+  fn test_climb() {
+    let graph = create_graph(HEIGHTMAP).unwrap();
 
-  ret
-}
+    let search = graph.climb([graph.start]);
+    assert_eq!(search.goal_cost(), Some(31));
+    assert_eq!(search.goal_path().map(|path| path.len()), Some(32));
 
-#[cfg(test)]
-mod tests {
-  use super::{get_edges, Graph, dijkstra};
+    assert_eq!(Day12::part2(&graph).unwrap(), "29");
+    assert!(Day12::part1(&create_graph("Sbz\nbcE").unwrap()).is_err());
+  }
 
   #[test]
-  fn test_dijkstra() {
-    let input = Graph {
-      width: 8,
-      height: 5,
-      vertices: vec![
-        b'a', b'a', b'b', b'q', b'p', b'o', b'n', b'm',
-        b'a', b'b', b'c', b'r', b'y', b'x', b'x', b'l',
-        b'a', b'c', b'c', b's', b'z', b'z', b'x', b'k',
-        b'a', b'c', b'c', b't', b'u', b'v', b'w', b'j',
-        b'a', b'b', b'd', b'e', b'f', b'g', b'h', b'i',
-      ],
-      edges: vec![
-        (false, true, true, false), (false, true, true, true), (false, false, true, true), (false, true, true, true), (false, true, false, true), (false, true, false, true), (false, true, false, true), (false, false, true, true),
-        (true, true, true, false), (true, true, true, true), (true, false, true, true), (true, false, true, true), (true, true, true, true), (true, true, false, true), (true, true, true, true), (true, false, true, false), 
-        (true, false, true, false), (true, true, true, true), (true, false, true, true), (true, false, true, true), (true, true, true, true), (true, true, true, true), (true, true, true, false), (true, false, true, false),
-        (true, false, true, false), (true, true, true, true), (true, false, true, true), (true, true, true, true), (false, true, true, true), (false, true, true, true), (true, true, true, true), (true, false, true, false),
-        (true, true, false, false), (true, false, false, true), (true, true, false, true), (false, true, false, true), (false, true, false, true), (false, true, false, true), (false, true, false, true), (true, false, false, true),
-      ],
-      start: 0,
-      end: 21,
-    };
-
-    assert_eq!(dijkstra::<false>(&input), 31);
+  fn test_shortest_path() {
+    let graph = create_graph(HEIGHTMAP).unwrap();
+
+    let path = graph.shortest_path([graph.start]).unwrap();
+    assert_eq!((path.len(), path.first(), path.last()), (32, Some(&(0, 0)), Some(&(5, 2))));
+    assert!(path.windows(2).all(|step| graph.edges(step[0]).any(|next| next == step[1])));
+
+    // Part 2 starts from the closest `a`, on the left edge.
+    let path = graph.shortest_path(graph.lowest()).unwrap();
+    assert_eq!((path.len(), path[0].0, graph.heights[path[0]]), (30, 0, b'a'));
+
+    let drawing = draw_path(&graph, &path, Style::new(false));
+    assert_eq!(drawing.lines().map(str::len).collect::<Vec<_>>(), [8; 5]);
+    assert_eq!(drawing.chars().filter(|c| "<>^v".contains(*c)).count(), 28);
+    assert_eq!(drawing.lines().nth(2).unwrap().chars().nth(5), Some('E'));
+
+    let rendering = Day12::render(&graph, Parts::One, Style::new(false)).unwrap().unwrap();
+    assert!(rendering.starts_with("Part 1: 31 steps from (0, 0) to (5, 2)\nPath: (0, 0) "));
+    assert!(!rendering.contains("Part 2"));
+    assert!(Day12::render(&create_graph("Sbz\nbcE").unwrap(), Parts::Both, Style::new(false)).is_err());
   }
-  
+
   #[test]
-  fn test_get_edges() {
-    let input = vec![
-      b'a', b'a', b'b', b'q', b'p', b'o', b'n', b'm',
-      b'a', b'b', b'c', b'r', b'y', b'x', b'x', b'l',
-      b'a', b'c', b'c', b's', b'z', b'z', b'x', b'k',
-      b'a', b'c', b'c', b't', b'u', b'v', b'w', b'j',
-      b'a', b'b', b'd', b'e', b'f', b'g', b'h', b'i',
-    ];
-
-    (0..40).for_each(|i| {
-      let (top, right, bottom, left) = get_edges(&input, 8, 5, i);
-
-      if i / 8 == 0 {
-        assert!(!top);
-      }
+  fn test_distances() {
+    let graph = create_graph(HEIGHTMAP).unwrap();
+    let distances = graph.distances();
 
-      if i % 8 == 7 {
-        assert!(!right);
-      }
+    // The backward search agrees with a forward search from every square.
+    for position in graph.heights.positions() {
+      assert_eq!(distances.to_end(position), graph.climb([position]).goal_cost());
+    }
 
-      if i / 8 == 4 {
-        assert!(!bottom);
-      }
+    assert_eq!(distances.nearest(|_, height| height == b'a'), Some(((0, 4), 29)));
+    assert_eq!(distances.nearest(|_, height| height == b'c'), Some(((2, 3), 26)));
+    assert_eq!(distances.nearest(|position, _| position.0 == 7), Some(((7, 0), 16)));
+    assert_eq!(distances.nearest(|_, height| height > b'z'), None);
 
-      if i % 8 == 0 {
-        assert!(!left);
-      }
+    let path = distances.path((0, 4)).unwrap();
+    assert_eq!((path.len(), path.first(), path.last()), (30, Some(&(0, 4)), Some(&(5, 2))));
+    assert!(path.windows(2).all(|step| graph.edges(step[0]).any(|next| next == step[1])));
 
-      let current = input[i];
+    // Nothing reaches the end past a cliff.
+    let graph = create_graph("Sbz\nbcE").unwrap();
+    let distances = graph.distances();
+    assert_eq!((distances.to_end((0, 0)), distances.nearest(|_, height| height == b'a')), (None, None));
+  }
 
-      if top {
-        let top = input[i - 8];
-        assert!(current >= top - 1);
-      }
+  #[test]
+  fn test_create_graph() {
+    let graph = create_graph(HEIGHTMAP).unwrap();
+    assert_eq!((graph.start, graph.end), ((0, 0), (5, 2)));
+    assert_eq!((graph.heights[graph.start], graph.heights[graph.end]), (b'a', b'z'));
+
+    // Rows of a different length than the first are rejected.
+    assert!(create_graph("SabE\nabc").is_err());
+    assert!(create_graph("abc\nabc").is_err());
+  }
 
-      if right {
-        let right = input[i + 1];
-        assert!(current >= right - 1);
-      }
+  #[test]
+  fn test_edges() {
+    let graph = create_graph(HEIGHTMAP).unwrap();
 
-      if bottom {
-        let bottom = input[i + 8];
-        assert!(current >= bottom - 1);
+    for (position, &height) in graph.heights.iter() {
+      for next in graph.edges(position) {
+        assert!(graph.heights[next] <= height + 1);
+        assert_eq!(position.0.abs_diff(next.0) + position.1.abs_diff(next.1), 1);
       }
+    }
 
-      if left {
-        let left = input[i - 1];
-        assert!(current >= left - 1);
-      }
-    })
+    // From the `c` at (2, 2) the `s` on the right is too high.
+    assert_eq!(graph.edges((2, 2)).collect::<Vec<_>>(), [(2, 1), (2, 3), (1, 2)]);
+    // The corners only have two neighbours.
+    assert_eq!(graph.edges((0, 0)).count(), 2);
   }
-}
\ No newline at end of file
+}
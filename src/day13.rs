@@ -1,236 +1,428 @@
-use std::{error::Error, fmt::{Debug, Formatter, self}, collections::VecDeque};
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
 
-use crate::utils::read_input_lines_ok;
+use nom::branch::alt;
+use nom::character::complete::{char, digit1};
+use nom::combinator::{cut, fail, map, map_res, peek};
+use nom::error::{context, VerboseError, VerboseErrorKind};
+use nom::multi::separated_list1;
+use nom::sequence::{preceded, terminated};
+use nom::{Err, IResult};
+use serde_json::Value;
 
-#[derive(Clone, PartialEq)]
-struct List(pub VecDeque<ListItem>);
+use crate::solution::{Solution, SolveResult};
+use crate::utils::SolveError;
 
-impl Debug for List {
-  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-    f.debug_list().entries(self.0.iter()).finish()
+/// A packet of the distress signal, or a value inside one: an integer or a
+/// list of values.
+///
+/// Equality is structural. The order of the puzzle is [`Packet::compare`],
+/// which puts an integer level with the list holding only that integer, so
+/// packets are not `Ord`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Packet {
+  Integer(u32),
+  List(Vec<Packet>),
+}
+
+/// The integers of the divider packets added in part 2.
+pub const DIVIDERS: [u32; 2] = [2, 6];
+
+impl Packet {
+  /// The packet `[[n]]`, as the divider packets are written.
+  pub fn divider(n: u32) -> Self {
+    Self::List(vec![Self::List(vec![Self::Integer(n)])])
+  }
+
+  /// Compares the packets as the puzzle does: lists item by item, then by
+  /// length, as slices do, and an integer as the list holding only it.
+  pub fn compare(&self, other: &Self) -> Ordering {
+    match (self, other) {
+      (Self::Integer(left), Self::Integer(right)) => left.cmp(right),
+      (Self::List(left), Self::List(right)) => compare_items(left, right),
+      (Self::Integer(_), Self::List(right)) => compare_items(std::slice::from_ref(self), right),
+      (Self::List(left), Self::Integer(_)) => compare_items(left, std::slice::from_ref(other)),
+    }
   }
 }
 
-#[derive(Clone, PartialEq)]
-enum ListItem {
-  Item(u32),
-  List(List),
+fn compare_items(left: &[Packet], right: &[Packet]) -> Ordering {
+  left
+    .iter()
+    .zip(right)
+    .map(|(left, right)| left.compare(right))
+    .find(|ordering| ordering.is_ne())
+    .unwrap_or_else(|| left.len().cmp(&right.len()))
 }
 
-impl ListItem {
-  pub fn to_u32(&self) -> u32 {
-    match self {
-      Self::Item(n) => *n,
-      Self::List(_) => panic!("Cannot unpack List"),
+impl From<&Packet> for Value {
+  fn from(packet: &Packet) -> Self {
+    match packet {
+      Packet::Integer(n) => Value::from(*n),
+      Packet::List(items) => Value::Array(items.iter().map(Value::from).collect()),
     }
   }
 }
 
-impl Debug for ListItem {
-  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-    write!(f, "{}", match self {
-      Self::Item(int) => int.to_string(),
-      Self::List(list) => format!("{list:?}"),
-    })
+/// Reads a JSON value made only of arrays and integers that fit in a `u32`.
+impl TryFrom<&Value> for Packet {
+  type Error = String;
+
+  fn try_from(value: &Value) -> Result<Self, String> {
+    match value {
+      Value::Number(number) => number
+        .as_u64()
+        .and_then(|n| u32::try_from(n).ok())
+        .map(Packet::Integer)
+        .ok_or_else(|| format!("Expected an integer between 0 and {}, found {number}", u32::MAX)),
+      Value::Array(items) => items.iter().map(Packet::try_from).collect::<Result<_, _>>().map(Packet::List),
+      value => Err(format!("Expected an integer or an array, found {value}")),
+    }
+  }
+}
+
+/// Prints the packet as it is written in the input.
+impl Display for Packet {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::Integer(n) => write!(f, "{n}"),
+      Self::List(items) => {
+        write!(f, "[")?;
+
+        for (i, item) in items.iter().enumerate() {
+          if i > 0 {
+            write!(f, ",")?;
+          }
+
+          write!(f, "{item}")?;
+        }
+
+        write!(f, "]")
+      },
+    }
   }
 }
 
-impl List {
-  pub fn new() -> Self {
-    List(VecDeque::from(vec![]))
+pub struct Day13;
+
+impl Solution for Day13 {
+  type Parsed = Vec<(Packet, Packet)>;
+
+  const DAY: usize = 13;
+  const TITLE: &'static str = "Distress Signal";
+
+  fn parse(input: &str) -> SolveResult<Self::Parsed> {
+    parse_pairs(input)
   }
 
-  pub fn from_vec(vec: Vec<ListItem>) -> Self {
-    List(VecDeque::from(vec))
+  fn part1(pairs: &Self::Parsed) -> SolveResult<String> {
+    Ok(sum_ordered_pairs(pairs).to_string())
   }
 
-  pub fn push(&mut self, item: ListItem) {
-    self.0.push_back(item);
+  fn part2(pairs: &Self::Parsed) -> SolveResult<String> {
+    Ok(decoder_key(pairs).to_string())
   }
 }
 
-pub fn solve() -> Result<(String, String), Box<dyn Error>> {
-  let input = read_input_lines_ok("day13.txt")?;
+/// Sums the indices, counted from 1, of the pairs whose left packet comes
+/// before the right one.
+pub fn sum_ordered_pairs(pairs: &[(Packet, Packet)]) -> usize {
+  pairs
+    .iter()
+    .enumerate()
+    .filter(|(_, (left, right))| left.compare(right).is_lt())
+    .map(|(index, _)| index + 1)
+    .sum()
+}
 
-  let pairs = parse_pairs(&input);
+/// Multiplies the positions of the dividers among the sorted packets.
+pub fn decoder_key(pairs: &[(Packet, Packet)]) -> usize {
+  let (_, dividers) = sort_with_dividers(pairs.iter().flat_map(|(left, right)| [left.clone(), right.clone()]));
+  dividers.iter().product()
+}
 
-  let part1 = pairs
+/// Sorts `packets` together with the divider packets. Returns the sorted
+/// packets and the positions of the dividers among them, counted from 1.
+pub fn sort_with_dividers(packets: impl IntoIterator<Item = Packet>) -> (Vec<Packet>, Vec<usize>) {
+  let mut packets = packets
     .into_iter()
-    .enumerate()
-    .fold(0, |mut count, (index, pair)| {
-      if compare_packets(pair) {
-        println!("Right: {}", index + 1);
-        count += index + 1;
-      }
+    .map(|packet| (packet, false))
+    .chain(DIVIDERS.map(|n| (Packet::divider(n), true)))
+    .collect::<Vec<_>>();
 
-      count
-  });
+  // The sort is stable, so a divider stays after the packets equal to it.
+  packets.sort_by(|(left, _), (right, _)| left.compare(right));
+
+  let dividers = packets
+    .iter()
+    .enumerate()
+    .filter(|(_, (_, divider))| *divider)
+    .map(|(index, _)| index + 1)
+    .collect();
 
-  let part2 = "";
-  
-  Ok((part1.to_string(), part2.to_string()))
+  (packets.into_iter().map(|(packet, _)| packet).collect(), dividers)
 }
 
-fn compare_lists(left: &mut List, right: &mut List) -> bool {
-  while let Some(mut left) = left.0.pop_front() {
-    let Some(mut right) = right.0.pop_front() else {
-      return false;
-    };
+/// How deep lists may nest in a packet, the outermost list being the first
+/// level. The parser recurses once per level, so deeper packets are rejected
+/// rather than allowed to overflow the stack.
+pub const MAX_DEPTH: usize = 100;
 
-    if !compare_list_items(&mut left, &mut right) {
-      return false;
-    }
+/// Parses a packet, which must be a list, written on its own line.
+pub fn parse_packet(line: &str) -> SolveResult<Packet> {
+  let parsed = context("Expected a packet starting with [", preceded(char('['), |input| list_items(input, 1)))(line);
+
+  match parsed {
+    Ok(("", items)) => Ok(Packet::List(items)),
+    Ok((rest, _)) => Err(SolveError::parse(line, token(rest), "Unexpected text after the packet")),
+    Err(Err::Error(error) | Err::Failure(error)) => {
+      // The innermost context names what was expected where parsing stopped.
+      let (rest, reason) = error
+        .errors
+        .iter()
+        .find_map(|(rest, kind)| match kind {
+          VerboseErrorKind::Context(reason) => Some((*rest, *reason)),
+          _ => None,
+        })
+        .unwrap_or((line, "Expected a packet"));
+
+      Err(SolveError::parse(line, token(rest), reason))
+    },
+    Err(Err::Incomplete(_)) => unreachable!("complete parsers never ask for more input"),
   }
-  
-  true
 }
 
-fn compare_list_items(left: &mut ListItem, right: &mut ListItem) -> bool {
-  match left {
-    item @ ListItem::Item(_) => match right {
-      ListItem::Item(right) => {
-        let ret = item.to_u32() <= *right;
-        // println!("1: {ret}");
-        ret
-      },
-      ListItem::List(right) => {
-        let ret = compare_lists(&mut List::from_vec(vec![item.clone()]), right);
-        // println!("2: {ret}");
-        ret
-      },
-    },
-    ListItem::List(left) => match right {
-      item @ ListItem::Item(_) => {
-        dbg!(&left);
-        dbg!(&item);
-        let ret = compare_lists(left, &mut List::from_vec(vec![item.clone()]));
-        // println!("3: {ret}");
-        ret 
-      },
-      ListItem::List(right) => {
-        let ret = compare_lists(left, right);
-        // println!("4: {ret}");
-        ret 
-      },
-    }
+type ParseResult<'a, T> = IResult<&'a str, T, VerboseError<&'a str>>;
+
+/// An integer or a list, inside a list `depth` levels deep.
+fn value(input: &str, depth: usize) -> ParseResult<'_, Packet> {
+  context(
+    "Expected an integer or a list",
+    alt((
+      map(preceded(char('['), |input| list_items(input, depth + 1)), Packet::List),
+      // Only digits too many for an integer are reported as a bad number.
+      map(preceded(peek(digit1), context("Expected a number", map_res(digit1, str::parse))), Packet::Integer),
+    )),
+  )(input)
+}
+
+/// The items of a list `depth` levels deep after its opening bracket, up to
+/// and including the closing one. Once the list is open, a malformed item is
+/// an error rather than the end of the list.
+fn list_items(input: &str, depth: usize) -> ParseResult<'_, Vec<Packet>> {
+  if depth > MAX_DEPTH {
+    return cut(context("Expected lists nested at most 100 deep", fail))(input);
   }
+
+  alt((
+    map(char(']'), |_| Vec::new()),
+    terminated(separated_list1(char(','), cut(|input| value(input, depth))), cut(context("Expected , or ]", char(']')))),
+  ))(input)
 }
 
-fn compare_packets((mut left, mut right): (List, List)) -> bool {
-  compare_lists(&mut left, &mut right)
+/// The text an error points at: the number or the character at the start of
+/// `rest`, or the empty text at the end of the line.
+fn token(rest: &str) -> &str {
+  let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+  let length = if digits > 0 { digits } else { rest.chars().next().map_or(0, char::len_utf8) };
+
+  &rest[..length]
 }
 
-fn parse_packet(line: &String) -> List {
-  line.chars().skip(1).fold((List::new(), Vec::<List>::new()), |(mut list, mut stack), char| {
-    if char == ',' {
-      return (list, stack);
-    } else if char.is_numeric() {
-      if let Some(last) = stack.last_mut() {
-        last.push(ListItem::Item(char as u32 - b'0' as u32));
-      } else {
-        list.push(ListItem::Item(char as u32 - b'0' as u32));
-      }
-      return (list, stack);
-    } else if char == '[' {
-      stack.push(List::new());
-      return (list, stack)
-    } else if char == ']' {
-      let Some(x) = stack.pop() else {
-        return (list, stack);
-      };
-      if let Some(last) = stack.last_mut() {
-        last.push(ListItem::List(x));
-      } else {
-        list.push(ListItem::List(x));
-      }
+/// Parses the pairs of packets, each on two lines, with a blank line between
+/// pairs. Blank lines at the end are ignored.
+pub fn parse_pairs(input: &str) -> SolveResult<Vec<(Packet, Packet)>> {
+  let lines = input.trim_end_matches(['\n', '\r']).lines().collect::<Vec<_>>();
 
-    }
-    (list, stack)
-  }).0
+  lines
+    .chunks(3)
+    .map(|chunk| match *chunk {
+      [left, right] | [left, right, ""] => {
+        let parse = |line| parse_packet(line).map_err(|error| error.within(input, line));
+        Ok((parse(left)?, parse(right)?))
+      },
+      [_, _, line] => Err(SolveError::parse(input, line, "Expected a blank line between pairs")),
+      [line] => Err(SolveError::parse(input, line, "Expected a pair of packets")),
+      _ => unreachable!(),
+    })
+    .collect()
 }
 
-fn parse_pairs(lines: &Vec<String>) -> Vec<(List, List)> {
-  let mut pairs = Vec::with_capacity((lines.len() + 1) * 2 / 3);
-  for i in (0..lines.len() + 1).step_by(3) {
-    pairs.push((parse_packet(&lines[i]), parse_packet(&lines[i + 1])));
-  }
+/// Parses one JSON array per line, as other tools write packets, skipping
+/// blank lines.
+pub fn parse_json_packets(input: &str) -> SolveResult<Vec<Packet>> {
+  input
+    .lines()
+    .filter(|line| !line.trim().is_empty())
+    .map(|line| {
+      let value = serde_json::from_str::<Value>(line)
+        .map_err(|error| SolveError::parse(input, line, format!("Invalid JSON ({error})")))?;
 
-  pairs
+      match value {
+        Value::Array(_) => Packet::try_from(&value).map_err(|reason| SolveError::parse(input, line, reason)),
+        _ => Err(SolveError::parse(input, line, "Expected a JSON array")),
+      }
+    })
+    .collect()
 }
 
 #[cfg(test)]
 mod tests {
-  use super::{List, ListItem, parse_packet, compare_packets, parse_pairs};
+  use std::cmp::Ordering;
+
+  use proptest::prelude::*;
+
+  use serde_json::{json, Value};
+
+  use super::{decoder_key, parse_json_packets, parse_packet, parse_pairs, sort_with_dividers, sum_ordered_pairs, Packet, MAX_DEPTH};
+
+  const PACKETS: &str = "\
+[1,1,3,1,1]
+[1,1,5,1,1]
+
+[[1],[2,3,4]]
+[[1],4]
+
+[9]
+[[8,7,6]]
+
+[[4,4],4,4]
+[[4,4],4,4,4]
+
+[7,7,7,7]
+[7,7,7]
+
+[]
+[3]
+
+[[[]]]
+[[]]
 
-  fn item(n: u32) -> ListItem {
-    ListItem::Item(n + b'0' as u32)
+[1,[2,[3,[4,[5,6,7]]]],8,9]
+[1,[2,[3,[4,[5,6,0]]]],8,9]";
+
+  fn int(n: u32) -> Packet {
+    Packet::Integer(n)
   }
 
-  fn list(v: Vec<ListItem>) -> ListItem {
-    ListItem::List(List::from_vec(v))
+  fn list(items: Vec<Packet>) -> Packet {
+    Packet::List(items)
   }
 
   #[test]
-  fn test_compare_packets() {
-    // assert!(compare_packets((parse_packet(&String::from("[[1],[2,3,4]]")), parse_packet(&String::from("[[1],4]")))))
+  fn test_parse_packet() {
+    assert_eq!(format!("{:?}", parse_packet("[10,[]]").unwrap()), "List([Integer(10), List([])])");
+    assert_eq!(parse_packet("[[1],[2,3,4]]").unwrap(), list(vec![list(vec![int(1)]), list(vec![int(2), int(3), int(4)])]));
+    assert_eq!(parse_packet("[[8,7,6]]").unwrap(), list(vec![list(vec![int(8), int(7), int(6)])]));
+
+    for (line, error) in [
+      ("[1,,2]", "<input>:1:4: Expected an integer or a list, found \",\""),
+      ("[1 2]", "<input>:1:3: Expected , or ], found \" \""),
+      ("[[1]", "<input>:1:5: Expected , or ]"),
+      ("[1]]", "<input>:1:4: Unexpected text after the packet, found \"]\""),
+      ("1", "<input>:1:1: Expected a packet starting with [, found \"1\""),
+      ("[99999999999]", "<input>:1:2: Expected a number, found \"99999999999\""),
+      ("[1,[2]x]", "<input>:1:7: Expected , or ], found \"x\""),
+      ("", "<input>:1:1: Expected a packet starting with ["),
+    ] {
+      assert_eq!(parse_packet(line).unwrap_err().to_string(), error, "{line}");
+    }
+  }
+
+  #[test]
+  fn test_parse_deep_packet() {
+    let nested = |depth: usize| format!("{}1{}", "[".repeat(depth), "]".repeat(depth));
+
+    let packet = parse_packet(&nested(MAX_DEPTH)).unwrap();
+    assert_eq!(packet.to_string(), nested(MAX_DEPTH));
+
+    let error = parse_packet(&nested(MAX_DEPTH + 1)).unwrap_err();
+    assert_eq!(error.to_string(), "<input>:1:102: Expected lists nested at most 100 deep, found \"1\"");
+
+    // Far deeper than the stack would allow without the limit.
+    assert!(parse_packet(&nested(1_000_000)).is_err());
+  }
+
+  /// Packets of up to four levels of lists, which are always lists at the top.
+  fn packets() -> impl Strategy<Value = Packet> {
+    any::<u32>()
+      .prop_map(Packet::Integer)
+      .prop_recursive(4, 64, 8, |item| prop::collection::vec(item, 0..8).prop_map(Packet::List))
+      .prop_map(|packet| match packet {
+        Packet::Integer(_) => Packet::List(vec![packet]),
+        list => list,
+      })
+  }
+
+  proptest! {
+    #[test]
+    fn test_display_round_trip(packet in packets()) {
+      let line = packet.to_string();
+      let parsed = parse_packet(&line).unwrap();
+
+      prop_assert_eq!(&parsed, &packet);
+      prop_assert_eq!(parsed.to_string(), line);
+    }
   }
 
   #[test]
   fn test_parse_pairs() {
-    let input = vec![
-      String::from("[1,1,3,1,1]"),
-      String::from("[1,1,5,1,1]"),
-      String::from(""),
-      String::from("[[1],[2,3,4]]"),
-      String::from("[[1],4]"),
-      // String::from("\n"),
-      // String::from("[9]\n"),
-      // String::from("[[8,7,6]]\n"),
-      // String::from("\n"),
-      // String::from("[[4,4],4,4]\n"),
-      // String::from("[[4,4],4,4,4]\n"),
-      // String::from("\n"),
-      // String::from("[7,7,7,7]\n"),
-      // String::from("[7,7,7]\n"),
-      // String::from("\n"),
-      // String::from("[]\n"),
-      // String::from("[3]\n"),
-      // String::from("\n"),
-      // String::from("[[[]]]\n"),
-      // String::from("[[]]\n"),
-      // String::from("\n"),
-      // String::from("[1,[2,[3,[4,[5,6,7]]]],8,9]\n"),
-      // String::from("[1,[2,[3,[4,[5,6,0]]]],8,9]"),
-    ];
-    
-    let expected = vec![
-      (List::from_vec(vec![item(1), item(1), item(3), item(1), item(1)]), List::from_vec(vec![item(1), item(1), item(5), item(1), item(1)])),
-      (List::from_vec(vec![list(vec![item(1)]), list(vec![item(2), item(3), item(4)])]), List::from_vec(vec![list(vec![item(1)]), item(4)])),
-    ];
-
-    assert_eq!(parse_pairs(&input), expected);
-  }
-  
+    let pairs = parse_pairs(PACKETS).unwrap();
+    assert_eq!(pairs.len(), 8);
+    assert_eq!(pairs[1], (parse_packet("[[1],[2,3,4]]").unwrap(), parse_packet("[[1],4]").unwrap()));
+
+    let error = parse_pairs("[1]\n[2]\n\n[3]\n[x]").unwrap_err();
+    assert_eq!(error.to_string(), "<input>:5:2: Expected an integer or a list, found \"x\"");
+    assert!(parse_pairs("[1]\n[2]\n[3]").is_err());
+    assert!(parse_pairs("[1]\n[2]\n\n[3]").is_err());
+
+    assert_eq!(parse_pairs(&format!("{PACKETS}\n\n\n")).unwrap(), pairs);
+    assert_eq!(parse_pairs("[1]\r\n[2]\r\n\r\n").unwrap().len(), 1);
+  }
+
   #[test]
-  fn test_parse_packet() {
-    use ListItem::Item;
-
-    let input = vec![
-      String::from("[1,1,3,1,1]"),
-      String::from("[[1],[2,3,4]]"),
-      String::from("[9]"),
-      String::from("[[8,7,6]]"),
-    ];
-
-    let expected = vec![
-      List::from_vec(vec![item(1), item(1), item(3), item(1), item(1)]),
-      List::from_vec(vec![list(vec![item(1)]), list(vec![item(2), item(3), item(4)])]),
-      List::from_vec(vec![item(9)]),
-      List::from_vec(vec![list(vec![item(8), item(7), item(6)])]),
-    ];
-
-    expected.iter().zip(input.iter()).for_each(|(expected, input)| {
-      assert_eq!(parse_packet(input), *expected);
-    });
-  }
-}
\ No newline at end of file
+  fn test_compare_packets() {
+    let ordered = parse_pairs(PACKETS).unwrap().iter().map(|(left, right)| left.compare(right).is_lt()).collect::<Vec<_>>();
+    assert_eq!(ordered, [true, true, false, true, false, true, false, false]);
+
+    // An integer compares as the list holding it without being equal to it,
+    // and the longer list of equal items comes last.
+    assert_eq!(int(3).compare(&list(vec![int(3)])), Ordering::Equal);
+    assert_ne!(int(3), list(vec![int(3)]));
+    assert!(list(vec![int(3)]).compare(&list(vec![list(vec![int(3)]), int(0)])).is_lt());
+    assert!(list(vec![]).compare(&list(vec![list(vec![])])).is_lt());
+  }
+
+  #[test]
+  fn test_solve() {
+    let pairs = parse_pairs(PACKETS).unwrap();
+    assert_eq!(sum_ordered_pairs(&pairs), 13);
+    assert_eq!(decoder_key(&pairs), 140);
+  }
+
+  #[test]
+  fn test_json() {
+    let packet = parse_packet("[1,[2,[]],10]").unwrap();
+    assert_eq!(Value::from(&packet), json!([1, [2, []], 10]));
+    assert_eq!(Packet::try_from(&json!([1, [2, []], 10])).unwrap(), packet);
+
+    assert_eq!(Packet::try_from(&json!([1, -2])).unwrap_err(), "Expected an integer between 0 and 4294967295, found -2");
+    assert_eq!(Packet::try_from(&json!([[1.5]])).unwrap_err(), "Expected an integer between 0 and 4294967295, found 1.5");
+    assert_eq!(Packet::try_from(&json!(["a"])).unwrap_err(), "Expected an integer or an array, found \"a\"");
+  }
+
+  #[test]
+  fn test_sort_json_packets() {
+    let packets = parse_json_packets("[1, [2]]\n\n[[6]]\n[]\n[ 3 ]\n").unwrap();
+    let (sorted, dividers) = sort_with_dividers(packets);
+
+    let lines = sorted.iter().map(Packet::to_string).collect::<Vec<_>>();
+    assert_eq!(lines, ["[]", "[1,[2]]", "[[2]]", "[3]", "[[6]]", "[[6]]"]);
+    // The divider comes after the packet equal to it.
+    assert_eq!(dividers, [3, 6]);
+
+    assert_eq!(parse_json_packets("[1]\n{}").unwrap_err().to_string(), "<input>:2:1: Expected a JSON array, found \"{}\"");
+    assert!(parse_json_packets("[1,").unwrap_err().to_string().starts_with("<input>:1:1: Invalid JSON"));
+    assert!(parse_json_packets("[-1]").is_err());
+  }
+}
@@ -1,14 +1,13 @@
-use std::{error::Error};
+use crate::solution::{Solution, SolveResult};
+use crate::utils::{parse_lines, SolveError};
 
-use crate::utils::{read_input_lines, SolveError};
 
-
-trait GetPoints {
+pub trait GetPoints {
   fn get_points(&self) -> u32;
 }
 
 #[derive(Debug, PartialEq)]
-enum Shape {
+pub enum Shape {
   Rock,
   Paper,
   Scissors,
@@ -25,7 +24,7 @@ impl GetPoints for Shape {
 }
 
 #[derive(Debug, PartialEq)]
-enum Round {
+pub enum Round {
   Victory(Shape),
   Draw(Shape),
   Defeat(Shape),
@@ -44,20 +43,43 @@ impl GetPoints for Round {
 use Shape::*;
 use Round::*;
 
-pub fn solve() -> Result<(String, String), Box<dyn Error>> {
-  let lines = read_input_lines("day2.txt")?;
-  
-  let sum: u32 = lines.into_iter().map(|l| parse_round(l.unwrap()).unwrap().get_points()).sum();
+pub struct Day2;
+
+impl Solution for Day2 {
+  /// Each round as read by part 1, where the second column is my shape, and
+  /// as read by part 2, where it is the result.
+  type Parsed = Vec<(Round, Round)>;
+
+  const DAY: usize = 2;
+  const TITLE: &'static str = "Rock Paper Scissors";
+
+  fn parse(input: &str) -> SolveResult<Self::Parsed> {
+    parse_lines(input, |line| Ok((parse_round(line)?, parse_round2(line)?)))
+  }
+
+  fn part1(rounds: &Self::Parsed) -> SolveResult<String> {
+    let sum: u32 = rounds.iter().map(|(round, _)| round.get_points()).sum();
+
+    Ok(sum.to_string())
+  }
 
-  let sum2: u32 = read_input_lines("day2.txt")?.map(|l| parse_round2(l.unwrap()).unwrap().get_points()).sum();
+  fn part2(rounds: &Self::Parsed) -> SolveResult<String> {
+    let sum: u32 = rounds.iter().map(|(_, round)| round.get_points()).sum();
 
-  Ok((sum.to_string(), sum2.to_string()))
+    Ok(sum.to_string())
+  }
+}
+
+fn split_round(line: &str) -> SolveResult<(&str, &str)> {
+  line
+    .split_once(' ')
+    .ok_or_else(|| SolveError::parse(line, line, "Expected two columns separated by a space"))
 }
 
-fn parse_round(line: String) -> Result<Round, SolveError> {
-  let (opponent, me) = line.split_at(1);
-  let opponent = parse_shape(opponent)?;
-  let me = parse_shape(me)?;
+pub fn parse_round(line: &str) -> SolveResult<Round> {
+  let (opponent, me) = split_round(line)?;
+  let opponent = parse_shape(opponent).map_err(|error| error.within(line, opponent))?;
+  let me = parse_shape(me).map_err(|error| error.within(line, me))?;
 
   let result = match opponent {
     Rock => match me {
@@ -80,10 +102,10 @@ fn parse_round(line: String) -> Result<Round, SolveError> {
   Ok(result)
 }
 
-fn parse_round2(line: String) -> Result<Round, SolveError> {
-  let (opponent, me) = line.split_at(1);
-  let opponent = parse_shape(opponent)?;
-  
+pub fn parse_round2(line: &str) -> SolveResult<Round> {
+  let (opponent, me) = split_round(line)?;
+  let opponent = parse_shape(opponent).map_err(|error| error.within(line, opponent))?;
+
   match me.trim() {
     "X" => Ok(Defeat(match opponent {
       Rock     => Scissors,
@@ -96,17 +118,16 @@ fn parse_round2(line: String) -> Result<Round, SolveError> {
       Paper    => Scissors,
       Scissors => Rock,
     })),
-    _   => Err(SolveError::new(&format!("Failed to parse round result: {}", me))),
+    _   => Err(SolveError::parse(line, me, "Expected a round result X, Y or Z")),
   }
 }
 
-fn parse_shape(s: &str) -> Result<Shape, SolveError> {
-  // println!("{}", s);
+pub fn parse_shape(s: &str) -> SolveResult<Shape> {
   match s.trim() {
     "A" | "X" => Ok(Rock),
     "B" | "Y" => Ok(Paper),
     "C" | "Z" => Ok(Scissors),
-    _         => Err(SolveError::new(&format!("Failed to parse to shape: {}", s))),
+    _         => Err(SolveError::parse(s, s, "Expected a shape A, B, C, X, Y or Z")),
   }
 }
 
@@ -123,7 +144,11 @@ mod tests {
   
   #[test]
   fn test_parse_round() {
-    assert_eq!(parse_round("A Y".into()), Ok(Victory(Paper)));
+    assert_eq!(parse_round("A Y"), Ok(Victory(Paper)));
+
+    let error = parse_round("A Q").unwrap_err();
+    assert_eq!(error.to_string(), "<input>:1:3: Expected a shape A, B, C, X, Y or Z, found \"Q\"");
+    assert!(parse_round("AY").is_err());
   }
 
   #[test]
@@ -135,8 +160,19 @@ mod tests {
 
   #[test]
   fn test_parse_round2() {
-    assert_eq!(parse_round2("A Y".into()), Ok(Draw(Rock)));
-    assert_eq!(parse_round2("B X".into()), Ok(Defeat(Rock)));
-    assert_eq!(parse_round2("C Z".into()), Ok(Victory(Rock)));
+    assert_eq!(parse_round2("A Y"), Ok(Draw(Rock)));
+    assert_eq!(parse_round2("B X"), Ok(Defeat(Rock)));
+    assert_eq!(parse_round2("C Z"), Ok(Victory(Rock)));
+  }
+
+  #[test]
+  fn test_parse() {
+    let rounds = Day2::parse("A Y\nB X\nC Z").unwrap();
+    assert_eq!(rounds[0], (Victory(Paper), Draw(Rock)));
+    assert_eq!(Day2::part1(&rounds).unwrap(), "15");
+    assert_eq!(Day2::part2(&rounds).unwrap(), "12");
+
+    let error = Day2::parse("A Y\nB Q").unwrap_err();
+    assert_eq!(error.to_string(), "<input>:2:3: Expected a shape A, B, C, X, Y or Z, found \"Q\"");
   }
 }
\ No newline at end of file
@@ -1,31 +1,56 @@
-use std::error::Error;
 use std::collections::{HashSet};
 
-use crate::utils::read_input_lines;
+use crate::solution::{Solution, SolveResult};
+use crate::utils::{parse_lines, SolveError};
 
-pub fn solve() -> Result<(String, String), Box<dyn Error>> {
-  let lines = read_input_lines("day3.txt")?.filter(|l| l.is_ok()).flatten().collect::<Vec<_>>();
-  
-  let part1: u32 = lines.iter().map(|l| get_points(get_dup_item(l))).sum();
+pub struct Day3;
+
+impl Solution for Day3 {
+  type Parsed = Vec<String>;
+
+  const DAY: usize = 3;
+  const TITLE: &'static str = "Rucksack Reorganization";
 
-  let group_of_three = group(&lines);
+  fn parse(input: &str) -> SolveResult<Self::Parsed> {
+    parse_lines(input, parse_rucksack)
+  }
+
+  fn part1(lines: &Self::Parsed) -> SolveResult<String> {
+    let part1: u32 = lines.iter().map(|l| get_points(get_dup_item(l))).sum();
 
-  let part2: u32 = unique(&group_of_three);
+    Ok(part1.to_string())
+  }
 
-  Ok((part1.to_string(), part2.to_string()))
+  fn part2(lines: &Self::Parsed) -> SolveResult<String> {
+    let group_of_three = group(lines);
+
+    let part2: u32 = unique(&group_of_three)?;
+
+    Ok(part2.to_string())
+  }
 }
 
-fn unique(group: &Vec<Vec<&String>>) -> u32 {
+pub fn unique(group: &[Vec<&String>]) -> SolveResult<u32> {
   group.iter().map(|v| {
+    let [first, second, third] = v[..] else {
+      return Err(SolveError::logic("Expected the number of rucksacks to be a multiple of three"));
+    };
+
     let mut set = HashSet::new();
     let mut set2 = HashSet::new();
-    v[0].chars().for_each(|c| { set.insert(c); });
-    v[1].chars().filter(|c| set.contains(&c)).for_each(|c| { set2.insert(c); });
-    get_points(v[2].chars().filter(|c| set2.contains(&c)).next().unwrap())
+    first.chars().for_each(|c| { set.insert(c); });
+    second.chars().filter(|c| set.contains(c)).for_each(|c| { set2.insert(c); });
+
+    let badge = third
+      .chars()
+      .find(|c| set2.contains(c))
+      .ok_or_else(|| SolveError::logic("Found no badge shared by a group of three rucksacks"))?;
+
+    Ok(get_points(badge))
   }).sum()
 }
 
-fn group(lines: &Vec<String>) -> Vec<Vec<&String>> {
+pub fn group(lines: &[String]) -> Vec<Vec<&String>> {
   lines.iter().enumerate().fold(Vec::new(), |mut v, (i, l)| {
     if i % 3 == 0 {
       v.push(Vec::new());
@@ -36,7 +61,21 @@ fn group(lines: &Vec<String>) -> Vec<Vec<&String>> {
   })
 }
 
-fn get_points(c: char) -> u32 {
+/// Checks that a rucksack holds only item types, split evenly over its two
+/// compartments.
+pub fn parse_rucksack(line: &str) -> SolveResult<String> {
+  if let Some(item) = line.matches(|c: char| !c.is_ascii_alphabetic()).next() {
+    return Err(SolveError::parse(line, item, "Expected an item type a-z or A-Z"));
+  }
+
+  if line.is_empty() || line.len() % 2 == 1 {
+    return Err(SolveError::parse(line, line, "Expected two compartments of the same size"));
+  }
+
+  Ok(line.to_string())
+}
+
+pub fn get_points(c: char) -> u32 {
   if c.is_uppercase() {
     c as u32 - 38
   } else {
@@ -44,7 +83,7 @@ fn get_points(c: char) -> u32 {
   }
 }
 
-fn get_dup_item(rucksack: &String) -> char {
+pub fn get_dup_item(rucksack: &str) -> char {
   let rucksack = rucksack.trim();
 
   let first_half = rucksack.chars().take(rucksack.len() / 2);
@@ -59,8 +98,9 @@ fn get_dup_item(rucksack: &String) -> char {
 }
 
 #[cfg(test)]
+#[allow(clippy::useless_vec, clippy::unnecessary_to_owned)]
 mod tests {
-  use super::{get_dup_item, get_points, group, unique};
+  use super::{get_dup_item, get_points, group, parse_rucksack, unique};
 
   #[test]
   fn test_unique() {
@@ -75,7 +115,9 @@ mod tests {
         &String::from("ttgJtRGJQctTZtZT"),
         &String::from("CrZsJsPPZsGzwwsLwLmpwMDw")
       ]
-    ]), 70);
+    ]), Ok(70));
+
+    assert!(unique(&[vec![&String::from("ab"), &String::from("cd")]]).is_err());
   }
     
   #[test]
@@ -85,6 +127,13 @@ mod tests {
     assert_eq!(get_dup_item(&"PmmdzqPrVvPwwTWBwg".to_string()), 'P');
   }
 
+  #[test]
+  fn test_parse_rucksack() {
+    assert_eq!(parse_rucksack("vJrwpWtwJgWr"), Ok(String::from("vJrwpWtwJgWr")));
+    assert_eq!(parse_rucksack("vJr").unwrap_err().to_string(), "<input>:1:1: Expected two compartments of the same size, found \"vJr\"");
+    assert_eq!(parse_rucksack("vJ-w").unwrap_err().to_string(), "<input>:1:3: Expected an item type a-z or A-Z, found \"-\"");
+  }
+
   #[test]
   fn test_get_points() {
     assert_eq!(get_points('A'), 27);
@@ -1,16 +1,30 @@
-use std::error::Error;
+use crate::solution::{Solution, SolveResult};
+use crate::utils::{parse_lines, parse_number, SolveError};
 
-use crate::utils::read_input_lines_ok;
+pub struct Day4;
 
-pub fn solve() -> Result<(String, String), Box<dyn Error>> {
-  let lines = read_input_lines_ok("day4.txt")?;
-  let parsed = lines.iter().map(|l| parse_pairs(l));
-  let count1 = parsed.clone().filter(|(left, right)| contains(left, right)).count();
-  let count2 = parsed.filter(|(left, right)| overlap(left, right)).count();
-  Ok((count1.to_string(), count2.to_string()))
+impl Solution for Day4 {
+  type Parsed = Vec<((u32, u32), (u32, u32))>;
+
+  const DAY: usize = 4;
+  const TITLE: &'static str = "Camp Cleanup";
+
+  fn parse(input: &str) -> SolveResult<Self::Parsed> {
+    parse_lines(input, parse_pairs)
+  }
+
+  fn part1(pairs: &Self::Parsed) -> SolveResult<String> {
+    let count = pairs.iter().filter(|(left, right)| contains(left, right)).count();
+    Ok(count.to_string())
+  }
+
+  fn part2(pairs: &Self::Parsed) -> SolveResult<String> {
+    let count = pairs.iter().filter(|(left, right)| overlap(left, right)).count();
+    Ok(count.to_string())
+  }
 }
 
-fn overlap(left: &(u32, u32), right: &(u32, u32)) -> bool {
+pub fn overlap(left: &(u32, u32), right: &(u32, u32)) -> bool {
   let (ls, le) = left;
   let (rs, re) = right;
   if ls == rs {
@@ -19,10 +33,10 @@ fn overlap(left: &(u32, u32), right: &(u32, u32)) -> bool {
   if ls < rs {
     return rs <= le;
   }
-  return ls <= re;
+  ls <= re
 }
 
-fn contains(left: &(u32, u32), right: &(u32, u32)) -> bool {
+pub fn contains(left: &(u32, u32), right: &(u32, u32)) -> bool {
   let (ls, le) = left;
   let (rs, re) = right;
   if ls == rs {
@@ -31,17 +45,26 @@ fn contains(left: &(u32, u32), right: &(u32, u32)) -> bool {
   if ls < rs {
     return le >= re;
   }
-  return re >= le;
+  re >= le
 }
 
-fn parse_range(s: &str) -> (u32, u32) {
-  let (start, end) = s.split_once('-').unwrap();
-  (start.parse().unwrap(), end.parse().unwrap())
+pub fn parse_range(s: &str) -> SolveResult<(u32, u32)> {
+  let Some((start, end)) = s.split_once('-') else {
+    return Err(SolveError::parse(s, s, "Expected a range like 2-4"));
+  };
+
+  Ok((parse_number(s, start)?, parse_number(s, end)?))
 }
 
-fn parse_pairs(line: &str) -> ((u32, u32), (u32, u32)) {
-  let (left, right) = line.split_once(',').unwrap();
-  (parse_range(left), parse_range(right))
+pub fn parse_pairs(line: &str) -> SolveResult<((u32, u32), (u32, u32))> {
+  let Some((left, right)) = line.split_once(',') else {
+    return Err(SolveError::parse(line, line, "Expected two ranges separated by a comma"));
+  };
+
+  let left_range = parse_range(left).map_err(|error| error.within(line, left))?;
+  let right_range = parse_range(right).map_err(|error| error.within(line, right))?;
+
+  Ok((left_range, right_range))
 }
 
 #[cfg(test)]
@@ -69,12 +92,16 @@ mod tests {
 
   #[test]
   fn test_parse_range() {
-    assert_eq!(parse_range("2-4"), (2, 4));
-    assert_eq!(parse_range("123-432"), (123, 432));
+    assert_eq!(parse_range("2-4"), Ok((2, 4)));
+    assert_eq!(parse_range("123-432"), Ok((123, 432)));
   }
 
   #[test]
   fn test_parse_pairs() {
-    assert_eq!(parse_pairs("2-4,6-8"), ((2, 4), (6, 8)));
+    assert_eq!(parse_pairs("2-4,6-8"), Ok(((2, 4), (6, 8))));
+
+    let error = parse_pairs("2-4,6-x").unwrap_err();
+    assert_eq!(error.to_string(), "<input>:1:7: Expected a number, found \"x\"");
+    assert!(parse_pairs("2-4").is_err());
   }
 }
\ No newline at end of file
@@ -1,73 +1,113 @@
 use regex::Regex;
 
-use std::error::Error;
 use std::collections::VecDeque;
 
-use crate::utils::read_input_lines_ok;
+use crate::solution::{Solution, SolveResult};
+use crate::utils::{input_lines, parse_number, SolveError};
 
 static REGEX_STACKS: &str = r"((\[(?P<letter>[A-Z])\]|(?P<space> {3})) ?)";
 static REGEX_TERMINATE: &str = r"^( [0-9]+ )( ( [0-9]+ ))*$";
 static REGEX_INSTRUCTION: &str = r"^move (?P<move>[0-9]+) from (?P<from>[0-9]+) to (?P<to>[0-9]+)$";
 
 #[derive(Debug, PartialEq)]
-struct Instruction(u8, u8, u8);
+pub struct Instruction(pub u8, pub u8, pub u8);
 
-pub fn solve() -> Result<(String, String), Box<dyn Error>> {
-  let lines = read_input_lines_ok("day5.txt")?;
+pub struct Day5;
 
-  let (mut stacks, index) = parse_stacks(&lines);
+impl Solution for Day5 {
+  type Parsed = (Vec<VecDeque<u8>>, Vec<Instruction>);
 
-  let instructions = parse_instructions(&lines[(index as usize)..]);
+  const DAY: usize = 5;
+  const TITLE: &'static str = "Supply Stacks";
 
-  let mut stacks2 = stacks.clone();
+  fn parse(input: &str) -> SolveResult<Self::Parsed> {
+    let lines = input_lines(input);
 
-  instructions.iter().for_each(|instruction| {
-    execute_instruction(&mut stacks, instruction);
-    execute_instruction2(&mut stacks2, instruction)
-  });
+    let (stacks, index) = parse_stacks(&lines)?;
 
-  Ok((get_top_of_stacks(&stacks), get_top_of_stacks(&stacks2)))
+    let instructions = parse_instructions(lines.get(index..).unwrap_or_default())
+      .map_err(|error| error.offset_lines(index))?;
+
+    Ok((stacks, instructions))
+  }
+
+  fn part1((stacks, instructions): &Self::Parsed) -> SolveResult<String> {
+    let mut stacks = stacks.clone();
+
+    instructions.iter().try_for_each(|instruction| execute_instruction(&mut stacks, instruction))?;
+
+    Ok(get_top_of_stacks(&stacks))
+  }
+
+  fn part2((stacks, instructions): &Self::Parsed) -> SolveResult<String> {
+    let mut stacks = stacks.clone();
+
+    instructions.iter().try_for_each(|instruction| execute_instruction2(&mut stacks, instruction))?;
+
+    Ok(get_top_of_stacks(&stacks))
+  }
 }
 
-fn get_top_of_stacks(stacks: &Vec<VecDeque<u8>>) -> String {
+/// The crates on top of the stacks. Empty stacks are skipped.
+pub fn get_top_of_stacks(stacks: &[VecDeque<u8>]) -> String {
   stacks.iter().fold(String::new(), |mut string, stack| {
-    let last = stack.len() - 1;
-    string.push(*stack.get(last).unwrap() as char);
+    if let Some(top) = stack.back() {
+      string.push(*top as char);
+    }
     string
   })
 }
 
-fn execute_instruction2(stacks: &mut Vec<VecDeque<u8>>, Instruction(m, f, t): &Instruction) {
-  let stack_from = stacks.get_mut(*f as usize - 1).unwrap();
-  let items = stack_from.split_off(stack_from.len() - *m as usize);
-  let stack_to = stacks.get_mut(*t as usize - 1).unwrap();
-  items.into_iter().for_each(|item| stack_to.push_back(item));
+fn stack_index(stacks: &[VecDeque<u8>], stack: u8) -> SolveResult<usize> {
+  match stack as usize {
+    n if (1..=stacks.len()).contains(&n) => Ok(n - 1),
+    n => Err(SolveError::logic(format!("Found no stack {n} among {} stacks", stacks.len()))),
+  }
 }
 
-fn execute_instruction(stacks: &mut Vec<VecDeque<u8>>, Instruction(m, f, t): &Instruction) {
-  (0..*m).for_each(|_| {
-    let stack_from = stacks.get_mut(*f as usize - 1).unwrap();
-    let item = stack_from.pop_back().unwrap();
-    let stack_to = stacks.get_mut(*t as usize - 1).unwrap();
-    stack_to.push_back(item);
-  });
+pub fn execute_instruction2(stacks: &mut [VecDeque<u8>], Instruction(m, f, t): &Instruction) -> SolveResult<()> {
+  let (from, to) = (stack_index(stacks, *f)?, stack_index(stacks, *t)?);
+  let stack_from = &mut stacks[from];
+  let Some(at) = stack_from.len().checked_sub(*m as usize) else {
+    return Err(SolveError::logic(format!("Cannot move {m} crates from stack {f}, which holds {}", stack_from.len())));
+  };
+  let items = stack_from.split_off(at);
+  items.into_iter().for_each(|item| stacks[to].push_back(item));
+  Ok(())
 }
 
-fn parse_instructions(lines: &[String]) -> Vec<Instruction> {
-  let regex = Regex::new(REGEX_INSTRUCTION).unwrap();
-  lines.iter().fold(Vec::new(), |mut vec, line| {
-    let captures = regex.captures(line).unwrap();
-    vec.push(
-      Instruction(
-        captures.name("move").unwrap().as_str().parse::<u8>().unwrap(),
-        captures.name("from").unwrap().as_str().parse::<u8>().unwrap(),
-        captures.name("to").unwrap().as_str().parse::<u8>().unwrap()
-      )
-    );
-    vec
+pub fn execute_instruction(stacks: &mut [VecDeque<u8>], Instruction(m, f, t): &Instruction) -> SolveResult<()> {
+  let (from, to) = (stack_index(stacks, *f)?, stack_index(stacks, *t)?);
+  (0..*m).try_for_each(|_| {
+    let item = stacks[from]
+      .pop_back()
+      .ok_or_else(|| SolveError::logic(format!("Cannot move a crate from the empty stack {f}")))?;
+    stacks[to].push_back(item);
+    Ok(())
   })
 }
 
+pub fn parse_instructions(lines: &[String]) -> SolveResult<Vec<Instruction>> {
+  let regex = Regex::new(REGEX_INSTRUCTION).unwrap();
+  lines
+    .iter()
+    .enumerate()
+    .map(|(index, line)| parse_instruction(line, &regex).map_err(|error| error.offset_lines(index)))
+    .collect()
+}
+
+fn parse_instruction(line: &str, regex: &Regex) -> SolveResult<Instruction> {
+  let captures = regex
+    .captures(line)
+    .ok_or_else(|| SolveError::parse(line, line, "Expected an instruction like move 1 from 2 to 3"))?;
+
+  Ok(Instruction(
+    parse_number(line, &captures["move"])?,
+    parse_number(line, &captures["from"])?,
+    parse_number(line, &captures["to"])?,
+  ))
+}
+
 fn init_stacks(line: &str) -> Vec<VecDeque<u8>> {
   let regex = Regex::new(REGEX_STACKS).unwrap();
   regex.captures_iter(line).fold(Vec::new(), |mut stacks, cap| {
@@ -83,26 +123,35 @@ fn init_stacks(line: &str) -> Vec<VecDeque<u8>> {
   })
 }
 
-fn parse_stacks(lines: &Vec<String>) -> (Vec<VecDeque<u8>>, u8) {
+/// Parses the drawing of the stacks, returning them together with the index
+/// of the first instruction.
+pub fn parse_stacks(lines: &[String]) -> SolveResult<(Vec<VecDeque<u8>>, usize)> {
   let regex_stacks = Regex::new(REGEX_STACKS).unwrap();
   let regex_terminate = Regex::new(REGEX_TERMINATE).unwrap();
-  let mut stacks = init_stacks(&lines[0]);
+  let Some(first) = lines.first() else {
+    return Err(SolveError::parse("", "", "Expected a drawing of the stacks"));
+  };
+  let mut stacks = init_stacks(first);
   let mut index = 2;
   for line in lines.iter().skip(1) {
     index += 1;
     if regex_terminate.is_match(line) {
-      break;
+      return Ok((stacks, index));
     }
-    regex_stacks.captures_iter(line).enumerate().for_each(|(i, cap)| {
+    for (i, cap) in regex_stacks.captures_iter(line).enumerate() {
       if let Some(letter) = &cap.name("letter") {
-        stacks[i].push_front(letter.as_str().as_bytes()[0]);
+        let Some(stack) = stacks.get_mut(i) else {
+          return Err(SolveError::parse(line, letter.as_str(), "Found a crate right of the stacks").offset_lines(index - 2));
+        };
+        stack.push_front(letter.as_str().as_bytes()[0]);
       }
-    });
+    }
   };
-  (stacks, index)
+  Err(SolveError::parse("", "", "Expected a line numbering the stacks").offset_lines(lines.len() - 1))
 }
 
 #[cfg(test)]
+#[allow(clippy::char_lit_as_u8)]
 mod tests {
   use std::collections::VecDeque;
 
@@ -118,7 +167,7 @@ mod tests {
 
     let instructions = Instruction(3, 1, 3);
 
-    execute_instruction2(&mut stacks, &instructions);
+    execute_instruction2(&mut stacks, &instructions).unwrap();
 
     let expected = vec![
       VecDeque::from(vec![]),
@@ -127,6 +176,9 @@ mod tests {
     ];
 
     assert_eq!(stacks, expected);
+
+    assert!(execute_instruction2(&mut stacks, &Instruction(2, 3, 4)).is_err());
+    assert!(execute_instruction2(&mut stacks, &Instruction(5, 3, 1)).is_err());
   }
   
   #[test]
@@ -150,7 +202,7 @@ mod tests {
 
     let instructions = Instruction(3, 1, 3);
 
-    execute_instruction(&mut stacks, &instructions);
+    execute_instruction(&mut stacks, &instructions).unwrap();
 
     let expected = vec![
       VecDeque::from(vec![]),
@@ -159,6 +211,9 @@ mod tests {
     ];
 
     assert_eq!(stacks, expected);
+
+    assert!(execute_instruction(&mut stacks, &Instruction(1, 1, 2)).is_err());
+    assert!(execute_instruction(&mut stacks, &Instruction(1, 0, 2)).is_err());
   }
   
   #[test]
@@ -176,7 +231,10 @@ mod tests {
       Instruction(2, 2, 1),
       Instruction(1, 1, 2),
     ];
-    assert_eq!(parse_instructions(input), output);
+    assert_eq!(parse_instructions(input), Ok(output));
+
+    let error = parse_instructions(&[String::from("move 1 from 2 to 1"), String::from("move 1 from x to 1")]).unwrap_err();
+    assert_eq!(error.to_string(), "<input>:2:1: Expected an instruction like move 1 from 2 to 3, found \"move 1 from x to 1\"");
   }
 
   #[test]
@@ -193,6 +251,7 @@ mod tests {
       VecDeque::from(vec![('M' as u8), ('C' as u8), ('D' as u8)]),
       VecDeque::from(vec![('P' as u8)]),
     ];
-    assert_eq!(parse_stacks(&input), (output , 5));
+    assert_eq!(parse_stacks(&input), Ok((output , 5)));
+    assert!(parse_stacks(&input[..3]).is_err());
   }
 }
\ No newline at end of file
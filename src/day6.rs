@@ -1,25 +1,45 @@
-use std::error::Error;
 use std::collections::HashMap;
 use std::cmp::max;
 
-use crate::utils::read_input_string;
+use crate::solution::{Solution, SolveResult};
+use crate::utils::SolveError;
 
-pub fn solve() -> Result<(String, String), Box<dyn Error>> {
-  let buffer = read_input_string("day6.txt")?;
-  
-  let mut map1: HashMap<char, usize> = HashMap::with_capacity(4);
-  let mut map2: HashMap<char, usize> = HashMap::with_capacity(14);
-  
-  let part1 = check_buffer(&buffer, 4, &mut map1);
-  let part2 = check_buffer(&buffer, 14, &mut map2);
+pub struct Day6;
+
+impl Solution for Day6 {
+  type Parsed = String;
+
+  const DAY: usize = 6;
+  const TITLE: &'static str = "Tuning Trouble";
+
+  fn parse(input: &str) -> SolveResult<Self::Parsed> {
+    let buffer = input.trim_end();
+
+    if let Some(char) = buffer.matches(|c: char| !c.is_ascii_lowercase()).next() {
+      return Err(SolveError::parse(buffer, char, "Expected a datastream of the letters a-z"));
+    }
+
+    Ok(buffer.to_string())
+  }
+
+  fn part1(buffer: &Self::Parsed) -> SolveResult<String> {
+    let mut map: HashMap<char, usize> = HashMap::with_capacity(4);
+
+    Ok(check_buffer(buffer, 4, &mut map)?.to_string())
+  }
+
+  fn part2(buffer: &Self::Parsed) -> SolveResult<String> {
+    let mut map: HashMap<char, usize> = HashMap::with_capacity(14);
 
-  Ok((part1.to_string(), part2.to_string()))
+    Ok(check_buffer(buffer, 14, &mut map)?.to_string())
+  }
 }
 
-fn check_buffer(buffer: &String, marker_length: usize, map: &mut HashMap<char, usize>) -> usize {
+pub fn check_buffer(buffer: &str, marker_length: usize, map: &mut HashMap<char, usize>) -> SolveResult<usize> {
   let mut start_index = 0;
+  let no_marker = || SolveError::logic(format!("Found no marker of {marker_length} distinct characters in the buffer of {}", buffer.len()));
   
-  if start_index + marker_length > buffer.len() { panic!("The size of the buffer ({}) is less than the start-of-packet-marker ({})", buffer.len(), marker_length); }
+  if start_index + marker_length > buffer.len() { return Err(no_marker()); }
   
   let mut slice = &buffer[start_index..(start_index + marker_length)];
 
@@ -27,12 +47,12 @@ fn check_buffer(buffer: &String, marker_length: usize, map: &mut HashMap<char, u
     start_index += offset;
     let end_index = start_index + marker_length;
 
-    if end_index > buffer.len() { panic!("The size of the buffer ({}) is less than the start-of-packet-marker ({})", buffer.len(), marker_length); }
+    if end_index > buffer.len() { return Err(no_marker()); }
 
     slice = &buffer[start_index..end_index];
   }
   
-  start_index + marker_length
+  Ok(start_index + marker_length)
 }
 
 fn check_slice(slice: &str, map: &mut HashMap<char, usize>) -> (bool, usize) {
@@ -52,6 +72,7 @@ fn check_slice(slice: &str, map: &mut HashMap<char, usize>) -> (bool, usize) {
 }
 
 #[cfg(test)]
+#[allow(clippy::needless_borrow)]
 mod tests {
   use std::collections::HashMap;
 
@@ -68,17 +89,19 @@ mod tests {
     let buffer4 = &String::from("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg");
     let buffer5 = &String::from("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw");
 
-    assert_eq!(check_buffer(&buffer1, 4, &mut map1), 7);
-    assert_eq!(check_buffer(&buffer2, 4, &mut map1), 5);
-    assert_eq!(check_buffer(&buffer3, 4, &mut map1), 6);
-    assert_eq!(check_buffer(&buffer4, 4, &mut map1), 10);
-    assert_eq!(check_buffer(&buffer5, 4, &mut map1), 11);
+    assert_eq!(check_buffer(&buffer1, 4, &mut map1), Ok(7));
+    assert_eq!(check_buffer(&buffer2, 4, &mut map1), Ok(5));
+    assert_eq!(check_buffer(&buffer3, 4, &mut map1), Ok(6));
+    assert_eq!(check_buffer(&buffer4, 4, &mut map1), Ok(10));
+    assert_eq!(check_buffer(&buffer5, 4, &mut map1), Ok(11));
     
-    assert_eq!(check_buffer(&buffer1, 14, &mut map2), 19);
-    assert_eq!(check_buffer(&buffer2, 14, &mut map2), 23);
-    assert_eq!(check_buffer(&buffer3, 14, &mut map2), 23);
-    assert_eq!(check_buffer(&buffer4, 14, &mut map2), 29);
-    assert_eq!(check_buffer(&buffer5, 14, &mut map2), 26);
+    assert_eq!(check_buffer(&buffer1, 14, &mut map2), Ok(19));
+    assert_eq!(check_buffer(&buffer2, 14, &mut map2), Ok(23));
+    assert_eq!(check_buffer(&buffer3, 14, &mut map2), Ok(23));
+    assert_eq!(check_buffer(&buffer4, 14, &mut map2), Ok(29));
+    assert_eq!(check_buffer(&buffer5, 14, &mut map2), Ok(26));
+
+    assert!(check_buffer("abcabc", 4, &mut map1).is_err());
   }
     
   #[test]
@@ -1,42 +1,59 @@
 use regex::Regex;
 
-use std::{collections::HashMap, error::Error};
+use std::collections::HashMap;
 
-use crate::utils::read_input_lines_ok;
+use crate::solution::{Solution, SolveResult};
+use crate::utils::{parse_lines, parse_number, SolveError};
 
 #[derive(PartialEq, Debug)]
-enum LineResult {
+pub enum LineResult {
     Cd(String),
     File(usize),
 }
 
-const REGEX_PARSE_LINE: &str = r"^(?P<cd>\$ cd ((\w|\.|/)+))|(?P<file>([0-9]+) (\w|\.)+)$";
+pub const REGEX_PARSE_LINE: &str = r"^(?P<cd>\$ cd ((\w|\.|/)+))|(?P<file>([0-9]+) (\w|\.)+)$";
 
-pub fn solve() -> Result<(String, String), Box<dyn Error>> {
-    let lines = read_input_lines_ok("day7.txt")?;
+pub struct Day7;
 
-    let regex = Regex::new(REGEX_PARSE_LINE).unwrap();
+impl Solution for Day7 {
+    type Parsed = HashMap<String, usize>;
 
-    let results: Vec<LineResult> = lines
-        .iter()
-        .filter_map(|line| parse_line(line, &regex))
-        .collect();
+    const DAY: usize = 7;
+    const TITLE: &'static str = "No Space Left On Device";
 
-    let dirs = get_dirs(results);
+    fn parse(input: &str) -> SolveResult<Self::Parsed> {
+        let regex = Regex::new(REGEX_PARSE_LINE).unwrap();
+
+        let results: Vec<LineResult> = parse_lines(input, |line| parse_line(line, &regex))?
+            .into_iter()
+            .flatten()
+            .collect();
 
-    let part1: usize = dirs.values().filter(|size| **size <= 100000).sum();
+        Ok(get_dirs(results))
+    }
 
-    let space_required = 30000000 - (70000000 - dirs.get(&String::from("/")).unwrap());
+    fn part1(dirs: &Self::Parsed) -> SolveResult<String> {
+        let part1: usize = dirs.values().filter(|size| **size <= 100000).sum();
 
-    let part2 = dirs
-        .values()
-        .filter(|v| **v > space_required)
-        .fold(usize::MAX, |min, val| std::cmp::min(*val, min));
+        Ok(part1.to_string())
+    }
 
-    Ok((part1.to_string(), part2.to_string()))
+    fn part2(dirs: &Self::Parsed) -> SolveResult<String> {
+        let used = dirs
+            .get(&String::from("/"))
+            .ok_or_else(|| SolveError::logic("Found no root directory"))?;
+        let space_required = (used + 30000000).saturating_sub(70000000);
+
+        let part2 = dirs
+            .values()
+            .filter(|v| **v > space_required)
+            .fold(usize::MAX, |min, val| std::cmp::min(*val, min));
+
+        Ok(part2.to_string())
+    }
 }
 
-fn get_dirs(results: Vec<LineResult>) -> HashMap<String, usize> {
+pub fn get_dirs(results: Vec<LineResult>) -> HashMap<String, usize> {
     let (_, map): (Vec<String>, HashMap<String, usize>) = results.iter().fold(
         (Vec::new(), HashMap::new()),
         |(mut current_path, mut map), lr| {
@@ -55,31 +72,29 @@ fn get_dirs(results: Vec<LineResult>) -> HashMap<String, usize> {
     map
 }
 
-fn add_to_dirs(current_path: &mut Vec<String>, map: &mut HashMap<String, usize>, size: usize) {
+fn add_to_dirs(current_path: &mut [String], map: &mut HashMap<String, usize>, size: usize) {
     (0..current_path.len()).for_each(|index| {
         let path = String::from(&current_path[..=index].join("/"));
         *map.entry(path).or_insert(0) += size;
     });
 }
 
-fn parse_line(line: &String, regex: &Regex) -> Option<LineResult> {
+/// Parses the lines that matter for the directory sizes, `None` for the
+/// others.
+pub fn parse_line(line: &str, regex: &Regex) -> SolveResult<Option<LineResult>> {
     let Some(captures) = regex.captures(line) else {
-    return None;
-  };
+        return Ok(None);
+    };
 
     if captures.name("cd").is_some() {
-        return Some(LineResult::Cd(
-            captures.get(2).unwrap().as_str().to_string(),
-        ));
+        return Ok(Some(LineResult::Cd(captures[2].to_string())));
     }
 
     if captures.name("file").is_some() {
-        return Some(LineResult::File(
-            captures.get(5).unwrap().as_str().parse().unwrap(),
-        ));
+        return Ok(Some(LineResult::File(parse_number(line, &captures[5])?)));
     }
 
-    None
+    Ok(None)
 }
 
 #[cfg(test)]
@@ -145,11 +160,13 @@ mod tests {
         let regex = Regex::new(REGEX_PARSE_LINE).unwrap();
         assert_eq!(
             parse_line(&String::from("$ cd .."), &regex),
-            Some(LineResult::Cd("..".to_string()))
+            Ok(Some(LineResult::Cd("..".to_string())))
         );
         assert_eq!(
             parse_line(&String::from("123 asd.asd"), &regex),
-            Some(LineResult::File(123))
+            Ok(Some(LineResult::File(123)))
         );
+        assert_eq!(parse_line(&String::from("$ ls"), &regex), Ok(None));
+        assert!(parse_line(&String::from("99999999999999999999999 big.file"), &regex).is_err());
     }
 }
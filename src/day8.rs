@@ -1,166 +1,101 @@
-use std::{error::Error, collections::HashSet};
-
-use crate::utils::read_input_string;
-
-pub fn solve() -> Result<(String, String), Box<dyn Error>> {
-  let input = read_input_string("day8.txt")?;
-
-  let (grid, side) = parse_trees(&input);
-  
-  let mut visible: HashSet<usize> = HashSet::with_capacity(side * side);
-
-  (1..side - 1).for_each(|i| {
-    (1..side - 1).fold((grid[side * i], grid[side * (i + 1) - 1]), |(mut left_max, mut right_max), j| {
-      let left_index = side * i + j;
-      let right_index = side * (i + 1) - j - 1;
-      
-      let left = grid[left_index];
-      let right = grid[right_index];
-
-      if left > left_max {
-        left_max = left;
-        visible.insert(left_index);
-      }
-
-      if right > right_max {
-        right_max = right;
-        visible.insert(right_index);
-      }
-      
-      (left_max, right_max)
-    });
-    
-    (1..side - 1).fold((grid[i], grid[side * (side - 1) + i]), |(mut top_max, mut bottom_max), j| {
-      let top_index = i + side * j;
-      let bottom_index = side * (side - 1 - j) + i;
-      
-      let top = grid[top_index];
-      let bottom = grid[bottom_index];
-
-      if top > top_max {
-        top_max = top;
-        visible.insert(top_index);
-      }
-
-      if bottom > bottom_max {
-        bottom_max = bottom;
-        visible.insert(bottom_index);
-      }
-
-      (top_max, bottom_max)
-    });
-  });
-
-  let part1 = visible.len() + 4 * (side - 1);
-
-  let part2 = (0..side).fold(0, |i_max, i| {
-    std::cmp::max((0..side).fold(0, |j_max, j| {
-      std::cmp::max(j_max, calculate_scenic_score(&grid, side, (i, j)))
-    }), i_max)
-  });
-
-  Ok((part1.to_string(), part2.to_string()))
-}
+use crate::solution::{Solution, SolveResult};
+use crate::utils::grid::Grid;
+use crate::utils::point::Direction;
 
-fn calculate_scenic_score(grid: &[u8], side: usize, pos: (usize, usize)) -> usize {
-  let top = distance_top(grid, side, pos);
-  let right = distance_right(grid, side, pos);
-  let bottom = distance_bottom(grid, side, pos);
-  let left = distance_left(grid, side, pos);
-  
-  top * right * bottom * left
-}
+pub struct Day8;
 
-fn distance_top(grid: &[u8], side: usize, (i, j): (usize, usize)) -> usize {
-  let tree_index = i + side * j;
-  let tree_height = grid[tree_index];
-  
-  for offset in 1..=j {
-    if grid[tree_index - side * offset] >= tree_height {
-      return offset;
-    }
-  }
-
-  j
-}
+impl Solution for Day8 {
+  type Parsed = Grid<u8>;
 
-fn distance_right(grid: &[u8], side: usize, (i, j): (usize, usize)) -> usize {
-  let tree_index = i + side * j;
-  let tree_height = grid[tree_index];
-  let max_offset = side - 1 - i;
+  const DAY: usize = 8;
+  const TITLE: &'static str = "Treetop Tree House";
 
-  for offset in 1..=max_offset {
-    if grid[tree_index + offset] >= tree_height {
-      return offset;
-    }
+  fn parse(input: &str) -> SolveResult<Self::Parsed> {
+    parse_trees(input)
   }
 
-  max_offset
-}
-
-fn distance_bottom(grid: &[u8], side: usize, (i, j): (usize, usize)) -> usize {
-  let tree_index = i + side * j;
-  let tree_height = grid[tree_index];
-  let max_offset = side - 1 -j;
-
-  for offset in 1..=max_offset {
-    if grid[tree_index + side * offset] >= tree_height {
-      return offset;
-    }
+  fn part1(grid: &Self::Parsed) -> SolveResult<String> {
+    Ok(count_visible(grid).to_string())
   }
 
-  max_offset
-}
-
-fn distance_left(grid: &[u8], side: usize, (i, j): (usize, usize)) -> usize {
-  let tree_index = i + side * j;
-  let tree_height = grid[tree_index];
-
-  for offset in 1..=i {
-    if grid[tree_index - offset] >= tree_height {
-      return offset;
-    }
+  fn part2(grid: &Self::Parsed) -> SolveResult<String> {
+    Ok(highest_scenic_score(grid).to_string())
   }
-
-  i
 }
 
-fn initialize_grid(string: &String) -> (Vec<u8>, usize) {
-  let side = string.find('\n').unwrap();
-  (Vec::with_capacity(side * side), side)
+/// Counts the trees that are taller than every tree between them and an edge
+/// of the grid, in at least one direction.
+pub fn count_visible(grid: &Grid<u8>) -> usize {
+  grid
+    .iter()
+    .filter(|&(position, height)| {
+      Direction::ALL
+        .into_iter()
+        .any(|direction| grid.ray(position, direction).all(|(_, tree)| tree < height))
+    })
+    .count()
 }
 
-fn parse_trees(string: &String) -> (Vec<u8>, usize) {
-  let (mut grid, side) = initialize_grid(string);
-  let j_length = side + 1;
-  let bytes = string.as_bytes();
+pub fn highest_scenic_score(grid: &Grid<u8>) -> usize {
+  grid.positions().map(|position| calculate_scenic_score(grid, position)).max().unwrap_or_default()
+}
 
-  (0..side).for_each(|i| {
-    let start = i * j_length;
-    let end = (i + 1) * j_length - 1;
-    grid.extend_from_slice(&bytes[start..end]);
-  });
+/// Multiplies the viewing distances from `position` in the four directions.
+/// The view stops at the first tree at least as tall, or at the edge.
+pub fn calculate_scenic_score(grid: &Grid<u8>, position: (usize, usize)) -> usize {
+  let height = grid[position];
+
+  Direction::ALL
+    .into_iter()
+    .map(|direction| {
+      let (Ok(distance) | Err(distance)) = grid
+        .ray(position, direction)
+        .try_fold(0, |distance, (_, tree)| if *tree < height { Ok(distance + 1) } else { Err(distance + 1) });
+
+      distance
+    })
+    .product()
+}
 
-  (grid, side)
+/// Parses a grid of tree heights, given as digits.
+pub fn parse_trees(string: &str) -> SolveResult<Grid<u8>> {
+  Grid::parse(string, |c| {
+    c.to_digit(10)
+      .map(|height| height as u8)
+      .ok_or_else(|| String::from("Expected a tree height 0-9"))
+  })
 }
 
 #[cfg(test)]
 mod tests {
-  use super::{initialize_grid, parse_trees};
+  use super::{calculate_scenic_score, count_visible, highest_scenic_score, parse_trees};
 
   #[test]
-  fn test_initialize_grid() {
+  fn test_parse_trees() {
     let input = String::from("30373\n25512\n65332\n33549\n35390");
-    let (grid, side) = initialize_grid(&input);
-    assert_eq!(side, 5);
-    assert_eq!(grid.capacity(), 25);
+    let expected = vec![3, 0, 3, 7, 3, 2, 5, 5, 1, 2, 6, 5, 3, 3, 2, 3, 3, 5, 4, 9, 3, 5, 3, 9, 0];
+    let grid = parse_trees(&input).unwrap();
+    assert_eq!((grid.width(), grid.height(), grid.cells()), (5, 5, expected.as_slice()));
+
+    let error = parse_trees("303\n2x5\n653").unwrap_err();
+    assert_eq!(error.to_string(), "<input>:2:2: Expected a tree height 0-9, found \"x\"");
+    assert!(parse_trees("303\n25\n653").is_err());
+    assert!(parse_trees("").is_err());
+
+    let grid = parse_trees("303\n255").unwrap();
+    assert_eq!((grid.width(), grid.height()), (3, 2));
   }
 
   #[test]
-  fn test_parse_trees() {
-    let input = String::from("30373\n25512\n65332\n33549\n35390");
-    // let expected = vec![b'5', b'5', b'1', b'5', b'3', b'3', b'3', b'5', b'4'];
-    let expected = vec![b'3', b'0', b'3', b'7', b'3', b'2', b'5', b'5', b'1', b'2', b'6', b'5', b'3', b'3', b'2', b'3', b'3', b'5', b'4', b'9', b'3', b'5', b'3', b'9', b'0'];
-    assert_eq!(parse_trees(&input), (expected, 5));
+  fn test_visible_and_scenic_score() {
+    let grid = parse_trees("30373\n25512\n65332\n33549\n35390").unwrap();
+    assert_eq!(count_visible(&grid), 21);
+    assert_eq!(calculate_scenic_score(&grid, (2, 3)), 8);
+    assert_eq!(highest_scenic_score(&grid), 8);
+
+    // Rectangular grids have their inner trees checked against both lengths.
+    let grid = parse_trees("3037\n2551\n6533").unwrap();
+    assert_eq!(count_visible(&grid), 12);
+    assert_eq!(highest_scenic_score(&grid), 1);
   }
-}
\ No newline at end of file
+}
@@ -1,23 +1,17 @@
-use regex::Regex;
-
-use std::{collections::{HashSet}, error::Error};
+use std::collections::HashSet;
 
-use crate::utils::read_input_lines_ok;
+use regex::Regex;
 
-type Coords = (i32, i32);
+use crate::solution::{Solution, SolveResult};
+use crate::utils::point::{Direction, Point2};
+use crate::utils::{parse_lines, parse_number, SolveError};
 
-#[derive(PartialEq, Clone, Copy)]
-enum Direction {
-  Up,
-  Right,
-  Down,
-  Left,
-}
+pub type Coords = Point2<i32>;
 
-#[derive(PartialEq, Clone, Copy)]
-struct Motion(Direction, isize);
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Motion(pub Direction, pub isize);
 
-struct Rope {
+pub struct Rope {
   knots: [Coords; 10],
   visited: HashSet<Coords>,
 }
@@ -25,7 +19,7 @@ struct Rope {
 impl Rope {
   pub fn new() -> Self {
     Self {
-      knots: [(0, 0); 10],
+      knots: [Coords::ORIGIN; 10],
       visited: HashSet::with_capacity(3000),
     }
   }
@@ -43,70 +37,65 @@ impl Rope {
   }
 
   fn step(&mut self, direction: Direction) {
-    let head = self.knots[0];
-    
-    let head = match direction {
-      Direction::Up    => (head.0, head.1 + 1),
-      Direction::Right => (head.0 + 1, head.1),
-      Direction::Down  => (head.0, head.1 - 1),
-      Direction::Left  => (head.0 - 1, head.1),
-    };
-
+    let head = self.knots[0] + direction.unit();
     self.knots[0] = head;
 
-    (1..10).fold(head, |head, i| {
-      let (tail, _) = match direction {
-        Direction::Up    => step_tail(self.knots[i], head),
-        Direction::Right => step_tail(self.knots[i], head),
-        Direction::Down  => step_tail(self.knots[i], head),
-        Direction::Left  => step_tail(self.knots[i], head),
-      };
-
-      self.knots[i] = tail;
-      
-      if i == 9 {
-        self.visited.insert(tail);
-      }
-
-      tail
+    let tail = (1..10).fold(head, |head, i| {
+      self.knots[i] = step_tail(self.knots[i], head);
+      self.knots[i]
     });
+
+    self.visited.insert(tail);
   }
 }
 
-const REGEX: &str = r"^(?P<direction>(U|R|D|L)) (?P<steps>\d+)$";
+impl Default for Rope {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+pub const REGEX: &str = r"^(?P<direction>(U|R|D|L)) (?P<steps>\d+)$";
+
+pub struct Day9;
 
-pub fn solve() -> Result<(String, String), Box<dyn Error>> {
-    let lines = read_input_lines_ok("day9.txt")?;
+impl Solution for Day9 {
+  type Parsed = Vec<Motion>;
 
+  const DAY: usize = 9;
+  const TITLE: &'static str = "Rope Bridge";
+
+  fn parse(input: &str) -> SolveResult<Self::Parsed> {
     let regex = Regex::new(REGEX).unwrap();
 
-    let mut visited: HashSet<Coords> = HashSet::with_capacity(6000);
+    parse_directions(input, &regex)
+  }
 
-    let motions = parse_directions(&lines, &regex);
+  fn part1(motions: &Self::Parsed) -> SolveResult<String> {
+    let mut visited: HashSet<Coords> = HashSet::with_capacity(6000);
 
-    motions.iter().fold(((0, 0), (0, 0)), |(tail, head), motion| {
+    motions.iter().fold((Coords::ORIGIN, Coords::ORIGIN), |(tail, head), motion| {
       apply_motion(tail, head, motion, &mut visited)
     });
 
-    let part1 = visited.len();
+    Ok(visited.len().to_string())
+  }
 
+  fn part2(motions: &Self::Parsed) -> SolveResult<String> {
     let mut rope = Rope::new();
 
     motions.iter().for_each(|motion| {
       rope.apply_motion(*motion);
     });
 
-    let part2 = rope.visited();
-
-    Ok((part1.to_string(), part2.to_string()))
+    Ok(rope.visited().to_string())
+  }
 }
 
-
-
-fn apply_motion(tail: Coords, head: Coords, motion: &Motion, visited: &mut HashSet<Coords>) -> (Coords, Coords) {
+pub fn apply_motion(tail: Coords, head: Coords, motion: &Motion, visited: &mut HashSet<Coords>) -> (Coords, Coords) {
   let Motion(direction, steps) = motion;
 
-  (1..=steps.abs() as usize).fold((tail, head), |(tail, head), _| {
+  (1..=steps.unsigned_abs()).fold((tail, head), |(tail, head), _| {
     let (tail, head) = step(tail, head, *direction);
     visited.insert(tail);
     (tail, head)
@@ -114,92 +103,102 @@ fn apply_motion(tail: Coords, head: Coords, motion: &Motion, visited: &mut HashS
 }
 
 fn step(tail: Coords, head: Coords, direction: Direction) -> (Coords, Coords) {
-   match direction {
-    Direction::Up    => step_tail(tail, (head.0, head.1 + 1)),
-    Direction::Right => step_tail(tail, (head.0 + 1, head.1)),
-    Direction::Down  => step_tail(tail, (head.0, head.1 - 1)),
-    Direction::Left  => step_tail(tail, (head.0 - 1, head.1)),
-  }
+  let head = head + direction.unit();
+  (step_tail(tail, head), head)
 }
 
-fn step_tail(tail: Coords, head: Coords) -> (Coords, Coords) {
-  let delta_x = head.0 - tail.0;
-  let delta_y = head.1 - tail.1;
-  
-  if delta_x.abs() > 1 {
-    if delta_y.abs() > 0 {
-      return ((tail.0 + 1 * delta_x.signum(), tail.1 + 1 * delta_y.signum()), head);
-    }
-
-    return ((tail.0 + 1 * delta_x.signum(), tail.1), head);
+/// Moves the tail one step towards the head, diagonally if needed, unless
+/// they are still touching.
+pub fn step_tail(tail: Coords, head: Coords) -> Coords {
+  if tail.chebyshev(head) > 1 {
+    tail + (head - tail).signum()
+  } else {
+    tail
   }
-
-  if delta_y.abs() > 1 {
-    if delta_x.abs() > 0 {
-      return ((tail.0 + 1 * delta_x.signum(), tail.1 + 1 * delta_y.signum()), head);
-    }
-
-    return ((tail.0, tail.1 + 1 * delta_y.signum()), head);
-  }
-
-  (tail, head) 
 }
 
-fn parse_directions(lines: &Vec<String>, regex: &Regex) -> Vec<Motion> {
-  lines.iter().map(|line| parse_direction(line, regex)).collect()
+pub fn parse_directions(input: &str, regex: &Regex) -> SolveResult<Vec<Motion>> {
+  parse_lines(input, |line| parse_direction(line, regex))
 }
 
-fn parse_direction(line: &String, regex: &Regex) -> Motion {
-  use Direction::*;
-
-  let captures = regex.captures(line).unwrap();
+pub fn parse_direction(line: &str, regex: &Regex) -> SolveResult<Motion> {
+  let captures = regex
+    .captures(line)
+    .ok_or_else(|| SolveError::parse(line, line, "Expected a motion like R 4"))?;
 
-  let steps = captures.name("steps").unwrap().as_str().parse::<isize>().unwrap();
+  let steps = parse_number::<isize>(line, &captures["steps"])?;
+  let direction = captures["direction"]
+    .chars()
+    .next()
+    .and_then(Direction::from_initial)
+    .ok_or_else(|| SolveError::parse(line, &captures["direction"], "Expected a direction U, R, D or L"))?;
 
-  match captures.name("direction").unwrap().as_str() {
-    "U" => Motion(Up, steps),
-    "R" => Motion(Right, steps),
-    "D" => Motion(Down, steps),
-    "L" => Motion(Left, steps),
-    _   => unreachable!(),
-  }
+  Ok(Motion(direction, steps))
 }
 
 #[cfg(test)]
 mod tests {
-  use super::{step_tail, step, Direction::*};
+  use regex::Regex;
+
+  use super::{parse_directions, step_tail, step, Coords, Direction::*, Motion, Rope, REGEX};
+
+  fn at(x: i32, y: i32) -> Coords {
+    Coords::new(x, y)
+  }
 
   #[test]
   fn test_step() {
     // Move head and tail follows (straight line)
-    assert_eq!(step((2, 3), (2, 4), Up), ((2, 4), (2, 5)));
-    assert_eq!(step((3, 1), (4, 1), Right), ((4, 1), (5, 1)));
-    assert_eq!(step((-1, 5), (-1, 4), Down), ((-1, 4), (-1, 3)));
-    assert_eq!(step((-2, -3), (-3, -3), Left), ((-3, -3), (-4, -3)));
+    assert_eq!(step(at(2, 3), at(2, 4), Up), (at(2, 4), at(2, 5)));
+    assert_eq!(step(at(3, 1), at(4, 1), Right), (at(4, 1), at(5, 1)));
+    assert_eq!(step(at(-1, 5), at(-1, 4), Down), (at(-1, 4), at(-1, 3)));
+    assert_eq!(step(at(-2, -3), at(-3, -3), Left), (at(-3, -3), at(-4, -3)));
 
     // Move head and tail follow (diagonally)
-    assert_eq!(step((3, 1), (4, 2), Up), ((4, 2), (4, 3)));
- 
+    assert_eq!(step(at(3, 1), at(4, 2), Up), (at(4, 2), at(4, 3)));
 
     // Move head and tail is stationary (same start coords)
-    assert_eq!(step((4, 5), (4, 5), Up), ((4, 5), (4, 6)));
-    assert_eq!(step((3, 4), (3, 4), Right), ((3, 4), (4, 4)));
-    assert_eq!(step((-2, 6), (-2, 6), Down), ((-2, 6), (-2, 5)));
-    assert_eq!(step((-1, 0), (-1, 0), Left), ((-1, 0), (-2, 0)));
+    assert_eq!(step(at(4, 5), at(4, 5), Up), (at(4, 5), at(4, 6)));
+    assert_eq!(step(at(3, 4), at(3, 4), Right), (at(3, 4), at(4, 4)));
+    assert_eq!(step(at(-2, 6), at(-2, 6), Down), (at(-2, 6), at(-2, 5)));
+    assert_eq!(step(at(-1, 0), at(-1, 0), Left), (at(-1, 0), at(-2, 0)));
   }
-  
+
   #[test]
   fn test_step_tail() {
-    assert_eq!(step_tail((0, 0), (0, 2)), ((0, 1), (0, 2)));
-    assert_eq!(step_tail((0, -1), (0, 1)), ((0, 0), (0, 1)));
-    assert_eq!(step_tail((-2, -3), (-2, -5)), ((-2, -4), (-2, -5)));
-    
-    assert_eq!(step_tail((2, 3), (1, 3)), ((2, 3), (1, 3)));
-    assert_eq!(step_tail((5, 6), (5, 7)), ((5, 6), (5, 7)));
-    assert_eq!(step_tail((-4, -2), (-4, -1)), ((-4, -2), (-4, -1)));
-
-    assert_eq!(step_tail((3, 2), (5, 2)), ((4, 2), (5, 2)));
-    assert_eq!(step_tail((-3, -5), (-5, -5)), ((-4, -5), (-5, -5)));
-    assert_eq!(step_tail((-1, 3), (1, 3)), ((0, 3), (1, 3)));
+    assert_eq!(step_tail(at(0, 0), at(0, 2)), at(0, 1));
+    assert_eq!(step_tail(at(0, -1), at(0, 1)), at(0, 0));
+    assert_eq!(step_tail(at(-2, -3), at(-2, -5)), at(-2, -4));
+
+    assert_eq!(step_tail(at(2, 3), at(1, 3)), at(2, 3));
+    assert_eq!(step_tail(at(5, 6), at(5, 7)), at(5, 6));
+    assert_eq!(step_tail(at(-4, -2), at(-4, -1)), at(-4, -2));
+
+    assert_eq!(step_tail(at(3, 2), at(5, 2)), at(4, 2));
+    assert_eq!(step_tail(at(-3, -5), at(-5, -5)), at(-4, -5));
+    assert_eq!(step_tail(at(-1, 3), at(1, 3)), at(0, 3));
+
+    // Knots further down a long rope can be two steps away on both axes.
+    assert_eq!(step_tail(at(0, 0), at(2, 2)), at(1, 1));
+  }
+
+  #[test]
+  fn test_rope() {
+    let regex = Regex::new(REGEX).unwrap();
+    let motions = parse_directions("R 5\nU 8\nL 8\nD 3\nR 17\nD 10\nL 25\nU 20", &regex).unwrap();
+
+    let mut rope = Rope::new();
+    motions.into_iter().for_each(|motion| rope.apply_motion(motion));
+    assert_eq!(rope.visited(), 36);
+  }
+
+  #[test]
+  fn test_parse_directions() {
+    let regex = Regex::new(REGEX).unwrap();
+
+    assert_eq!(parse_directions("R 4\nU 12", &regex), Ok(vec![Motion(Right, 4), Motion(Up, 12)]));
+
+    let error = parse_directions("R 4\nX 1", &regex).unwrap_err();
+    assert_eq!(error.to_string(), "<input>:2:1: Expected a motion like R 4, found \"X 1\"");
   }
-}
\ No newline at end of file
+}
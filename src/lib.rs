@@ -0,0 +1,66 @@
+//! Solutions to [Advent of Code 2022](https://adventofcode.com/2022).
+//!
+//! Every day lives in its own `dayN` module and implements
+//! [`Solution`](solution::Solution). The registered solutions can be looked up
+//! by day through [`solver`] or iterated through [`SOLVERS`].
+
+use solution::Solver;
+
+pub mod answers;
+pub mod bench;
+pub mod client;
+pub mod output;
+pub mod scaffold;
+pub mod solution;
+pub mod submit;
+pub mod utils;
+pub mod watch;
+
+/// Declares the day modules and registers their solutions. Adding a day only
+/// requires a new entry here.
+macro_rules! days {
+  ($($module:ident::$solution:ident),* $(,)?) => {
+    $(pub mod $module;)*
+
+    /// The solutions of every day, in order.
+    pub const SOLVERS: &[&dyn Solver] = &[$(&$module::$solution),*];
+  };
+}
+
+days! {
+  day1::Day1,     day2::Day2,     day3::Day3,     day4::Day4,
+  day5::Day5,     day6::Day6,     day7::Day7,     day8::Day8,
+  day9::Day9,     day10::Day10,   day11::Day11,   day12::Day12,
+  day13::Day13,
+}
+
+/// The registered solution of `day`, if any.
+pub fn solver(day: usize) -> Option<&'static dyn Solver> {
+  SOLVERS.iter().copied().find(|solver| solver.day() == day)
+}
+
+/// The solutions of a comma separated list of days and inclusive ranges of
+/// days, such as `3,7,11-13`, in the order given. Repeated days are kept once.
+pub fn select(days: &str) -> Result<Vec<&'static dyn Solver>, String> {
+  let mut solvers: Vec<&'static dyn Solver> = Vec::new();
+
+  for item in days.split(',') {
+    let (start, end) = item.split_once('-').unwrap_or((item, item));
+    let parse = |day: &str| day.trim().parse::<usize>().map_err(|_| format!("{item} is not a day or a range of days"));
+    let (start, end) = (parse(start)?, parse(end)?);
+
+    if start > end {
+      return Err(format!("The range {item} is empty"));
+    }
+
+    for day in start..=end {
+      let solver = solver(day).ok_or_else(|| format!("No solution is registered for day {day}"))?;
+
+      if solvers.iter().all(|selected| selected.day() != day) {
+        solvers.push(solver);
+      }
+    }
+  }
+
+  Ok(solvers)
+}
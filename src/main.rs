@@ -1,93 +1,691 @@
-use std::{error::Error, time::Instant};
-
-mod utils;
-mod day1;   mod day9;
-mod day2;   mod day10;
-mod day3;   mod day11;
-mod day4;   mod day12;
-mod day5;   mod day13;
-mod day6;
-mod day7;
-mod day8;
-
-type SolverResult = Result<(String, String), Box<dyn Error>>;
-type Solver = fn() -> SolverResult;
-
-const DAYS: usize = 13;
-const SOLVERS: [Solver; DAYS] = [
-    day1::solve,    day2::solve,    day3::solve,    day4::solve,
-    day5::solve,    day6::solve,    day7::solve,    day8::solve,
-    day9::solve,    day10::solve,   day11::solve,   day12::solve,
-    day13::solve,
-];
-
-fn main() {
-    let args = std::env::args().collect::<Vec<_>>();
-
-    let Some(command) = args.get(1) else {
-        eprintln!("A command must be specified. Available: day, all.");
-        return;
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode};
+use std::thread;
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use aoc2022::answers::{check, Answers, Check};
+use aoc2022::bench::{self, change, is_regression, load_baseline, save_baseline, Baseline, Options};
+use aoc2022::client::{read_or_fetch, Client, ClientError};
+use aoc2022::day11::{exact_throws, throws, Day11};
+use aoc2022::day13::{parse_json_packets, sort_with_dividers, Packet, DIVIDERS};
+use aoc2022::output::{write_records, Format, Record, Style};
+use aoc2022::scaffold::scaffold;
+use aoc2022::solution::{self, run_parallel, Parts, Report, Solution, SolveResult, Solver};
+use aoc2022::submit::{History, Outcome, Verdict, HISTORY_FILE};
+use aoc2022::utils::Input;
+use aoc2022::watch::{diff, parse_runs, Run, Watcher};
+use aoc2022::{select, solver, SOLVERS};
+
+const COMMANDS: &str = "day, all, verify, bench, fetch, submit, watch, new, sort-packets, exact-monkeys";
+
+fn main() -> ExitCode {
+    match run(std::env::args().collect()) {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("{error}");
+            ExitCode::FAILURE
+        },
+    }
+}
+
+fn run(mut args: Vec<String>) -> Result<ExitCode, String> {
+    let example = take_flag(&mut args, "--example");
+
+    let input = match (take_option(&mut args, "--input")?, example) {
+        (Some(_), true) => return Err(String::from("--input cannot be combined with --example")),
+        (Some(path), false) => Input::from_arg(&path),
+        (None, true) => Input::examples_dir().map_err(|error| format!("Failed to locate the examples directory: {error}"))?,
+        (None, false) => Input::default_dir().map_err(|error| format!("Failed to locate the input directory: {error}"))?,
+    };
+
+    let base_url = take_option(&mut args, "--base-url")?;
+    let format = take_parsed(&mut args, "--format")?.unwrap_or_default();
+    let answers = take_option(&mut args, "--answers")?.map(PathBuf::from);
+
+    let jobs = take_parsed(&mut args, "--jobs")?.unwrap_or(1);
+    if jobs == 0 {
+        return Err(String::from("--jobs must be at least 1"));
+    }
+
+    let Some(command) = args.get(1).cloned() else {
+        return Err(format!("A command must be specified. Available: {COMMANDS}."));
     };
 
+    if command == "fetch" {
+        return run_fetch(&mut args, &input, example, base_url);
+    }
+
+    // Missing inputs are downloaded when a session token is configured, but
+    // never into the examples directory.
+    let client = (!example).then(|| Client::from_env(base_url.clone()).ok()).flatten();
+    let source = Source { input, client };
+    let input = &source.input;
+
     match command.as_str() {
-        "all" => run_all(),
+        "all" => {
+            expect_dir(input, "all")?;
+            run_all(&source, format, jobs);
+        },
         "day" => {
-            let Some(day) = args.get(2) else {
-                eprintln!("A day must be specified.");
-                return;
+            let parts = take_parsed(&mut args, "--part")?.unwrap_or_default();
+            let render = take_flag(&mut args, "--render");
+            if render && format != Format::Table {
+                return Err(String::from("--render cannot be combined with --format json or csv"));
+            }
+
+            let Some(days) = args.get(2) else {
+                return Err(String::from("A day must be specified."));
             };
 
-            let Ok(day) = day.parse::<usize>() else {
-                eprintln!("An integer between 1 and {DAYS} must be specified (received {day})");
-                return;
+            let solvers = parse_days(days)?;
+            if solvers.len() > 1 {
+                expect_dir(input, "day with several days")?;
+            }
+
+            let inputs = run_days(&solvers, &source, format, parts, jobs);
+
+            if render && !render_days(&solvers, &inputs, &source, parts) {
+                return Ok(ExitCode::FAILURE);
+            }
+        },
+        "verify" => {
+            expect_dir(input, "verify")?;
+
+            let path = answers.unwrap_or_else(|| match input {
+                Input::Path(dir) if example => dir.join("answers.toml"),
+                _ => PathBuf::from("answers.toml"),
+            });
+            let answers = Answers::load(&path)
+                .map_err(|error| format!("Failed to read the answers from {}: {error}", path.display()))?;
+
+            return Ok(run_verify(&source, &answers, jobs));
+        },
+        "bench" => {
+            let defaults = Options::default();
+            let options = Options {
+                warmup: take_parsed(&mut args, "--warmup")?.unwrap_or(defaults.warmup),
+                iterations: take_parsed(&mut args, "--iterations")?.unwrap_or(defaults.iterations),
+            };
+            let threshold = take_parsed(&mut args, "--threshold")?.unwrap_or(10.0);
+
+            let baseline = take_option(&mut args, "--baseline")?
+                .map(|path| load_baseline(path.as_ref()).map_err(|error| format!("Failed to read the baseline from {path}: {error}")))
+                .transpose()?;
+
+            let save = take_option(&mut args, "--save-baseline")?;
+
+            let solvers = match args.get(2) {
+                Some(days) => parse_days(days)?,
+                None => {
+                    expect_dir(input, "bench")?;
+                    SOLVERS.to_vec()
+                },
+            };
+
+            let (results, regressed) = run_bench(&solvers, &source, options, baseline.as_ref(), threshold);
+
+            if let Some(path) = save {
+                save_baseline(path.as_ref(), &results).map_err(|error| format!("Failed to save the baseline to {path}: {error}"))?;
+                println!("Saved the baseline to {path}");
+            }
+
+            if regressed {
+                return Ok(ExitCode::FAILURE);
+            }
+        },
+        "watch" => return run_watch(&mut args, input),
+        "new" => {
+            let title = take_option(&mut args, "--title")?;
+
+            let Some(day) = args.get(2) else {
+                return Err(String::from("A day must be specified."));
             };
+            let day = day
+                .parse()
+                .ok()
+                .filter(|day| (1..=25).contains(day))
+                .ok_or_else(|| format!("{day} is not a day between 1 and 25"))?;
+            let title = title.unwrap_or_else(|| format!("Day {day}"));
+
+            let changed = scaffold(Path::new(env!("CARGO_MANIFEST_DIR")), day, &title)
+                .map_err(|error| format!("Failed to create day {day}: {error}"))?;
 
-            if day > DAYS {
-                eprintln!("An integer between 1 and {DAYS} must be specified (received {day})");
-                return;
+            changed.iter().for_each(|path| println!("Wrote {}", path.display()));
+        },
+        "sort-packets" => {
+            // A file given after the command takes the place of the day 13 input.
+            let file = args.get(2).map(|path| Input::from_arg(path));
+            run_sort_packets(file.as_ref(), &source).map_err(|error| error.to_string())?;
+        },
+        "exact-monkeys" => {
+            let rounds = take_parsed(&mut args, "--rounds")?.unwrap_or(20);
+            // A file given after the command takes the place of the day 11 input.
+            let file = args.get(2).map(|path| Input::from_arg(path));
+            return run_exact_monkeys(file.as_ref(), &source, rounds).map_err(|error| error.to_string());
+        },
+        "submit" => {
+            if example {
+                return Err(String::from("submit cannot be combined with --example"));
             }
 
-            run_day(day);
+            let client = Client::from_env(base_url).map_err(|error| error.to_string())?;
+            return run_submit(&mut args, &source, &client);
         },
-        rec => eprintln!("{rec} is not a valid command. Available commans are: day, all."),
+        rec => return Err(format!("{rec} is not a valid command. Available commans are: {COMMANDS}.")),
     }
+
+    Ok(ExitCode::SUCCESS)
 }
 
-fn run_all() {
-    let start_total = Instant::now();
+/// Where the inputs are read from, and the client that downloads the missing
+/// ones.
+struct Source {
+    input: Input,
+    client: Option<Client>,
+}
 
-    println!("\x1b[1mDay\x1b[0m | \x1b[94mPart 1\x1b[0m       | \x1b[93mPart2\x1b[0m        | Time (ms)");
-    
-    for i in 0..DAYS {
-        let start_solution = Instant::now();
-        let solution = SOLVERS[i]();
-        let elapsed_solution = start_solution.elapsed().as_micros();
-        let time = (elapsed_solution as f64) / 1000.0;
-        match solution {
-            Ok((part1, part2)) => println!("\x1b[1m{:<2}\x1b[0m  | {part1:12} | {part2:12} | {time:07.3}", i + 1),
-            Err(error) => eprintln!("\x1b[91mError on Day {:2}\x1b[0m: {error}", i + 1),
-        }
+impl Source {
+    fn read(&self, day: usize) -> SolveResult<String> {
+        read_or_fetch(&self.input, day, self.client.as_ref())
+    }
+}
+
+fn expect_dir(input: &Input, command: &str) -> Result<(), String> {
+    if matches!(input, Input::Path(path) if path.is_dir()) {
+        Ok(())
+    } else {
+        Err(format!("The input of {command} must be a directory containing dayN.txt files (received {input})"))
     }
+}
 
-    let elapsed_total = start_total.elapsed().as_millis();
+fn parse_days(days: &str) -> Result<Vec<&'static dyn Solver>, String> {
+    select(days).map_err(|error| format!("{error} (available: {})", available_days()))
+}
 
-    println!("\nTotal Time Elapsed: {elapsed_total} ms");
+fn available_days() -> String {
+    SOLVERS.iter().map(|solver| solver.day().to_string()).collect::<Vec<_>>().join(", ")
+}
+
+/// Removes the flag `name` from the arguments and returns whether it was set.
+fn take_flag(args: &mut Vec<String>, name: &str) -> bool {
+    let Some(index) = args.iter().position(|arg| arg == name) else {
+        return false;
+    };
+
+    args.remove(index);
+    true
+}
+
+/// Removes `name <value>` from the arguments and returns the value, if present.
+fn take_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>, String> {
+    let Some(index) = args.iter().position(|arg| arg == name) else {
+        return Ok(None);
+    };
+
+    if index + 1 >= args.len() {
+        return Err(format!("{name} requires a value"));
+    }
+
+    let value = args.remove(index + 1);
+    args.remove(index);
+    Ok(Some(value))
+}
+
+fn take_parsed<T: FromStr>(args: &mut Vec<String>, name: &str) -> Result<Option<T>, String>
+where
+    T::Err: std::fmt::Display,
+{
+    take_option(args, name)?
+        .map(|value| value.parse().map_err(|error| format!("Invalid value for {name} ({value}): {error}")))
+        .transpose()
+}
+
+/// Reads and solves a day, naming the input file in its errors.
+fn solve_day(solver: &dyn Solver, source: &Source, parts: Parts) -> SolveResult<Report> {
+    read_and_solve_day(solver, source, parts).0
+}
+
+/// Like [`solve_day`], also returning the input when it could be read, as
+/// stdin cannot be read a second time.
+fn read_and_solve_day(solver: &dyn Solver, source: &Source, parts: Parts) -> (SolveResult<Report>, Option<String>) {
+    let day = solver.day();
+    let string = match source.read(day) {
+        Ok(string) => string,
+        Err(error) => return (Err(error.in_file(source.input.name(day))), None),
+    };
+
+    let report = solution::run_parts(solver, &string, parts).map_err(|error| error.in_file(source.input.name(day)));
+    (report, Some(string))
+}
+
+fn print_records(format: Format, records: &[Record], wall: Duration) {
+    if let Err(error) = write_records(&mut io::stdout(), &mut io::stderr(), format, Style::detect(&io::stdout()), Style::detect(&io::stderr()), records, wall) {
+        eprintln!("Failed to print the results: {error}");
+    }
 }
 
-fn run_day(day: usize) {
+fn run_all(source: &Source, format: Format, jobs: usize) {
     let start = Instant::now();
-    
-    println!("Running \x1b[1mDay {day}\x1b[0m");
-    
-    println!("\x1b[94mPart 1\x1b[0m       | \x1b[93mPart2\x1b[0m        | Time (ms)");
+    let records = run_parallel(SOLVERS, jobs, |solver| Record::new(solver, solve_day(solver, source, Parts::Both)));
+    let wall = start.elapsed();
+
+    print_records(format, &records, wall);
+}
+
+/// Solves the days and prints their records. Returns the input of each day,
+/// `None` when it could not be read.
+fn run_days(solvers: &[&dyn Solver], source: &Source, format: Format, parts: Parts, jobs: usize) -> Vec<Option<String>> {
+    if let ([solver], Format::Table) = (solvers, format) {
+        println!("Running {}: {}", Style::detect(&io::stdout()).paint("1", format!("Day {}", solver.day())), solver.title());
+    }
+
+    let start = Instant::now();
+    let results = run_parallel(solvers, jobs, |solver| {
+        let (result, input) = read_and_solve_day(solver, source, parts);
+        (Record::new(solver, result), input)
+    });
+    let wall = start.elapsed();
+
+    let (records, inputs): (Vec<_>, Vec<_>) = results.into_iter().unzip();
+    print_records(format, &records, wall);
+
+    inputs
+}
+
+/// Prints how each day found its answers from the `inputs` it was solved
+/// with, for the days that can draw it. Returns whether every day that was
+/// read could be rendered.
+fn render_days(solvers: &[&dyn Solver], inputs: &[Option<String>], source: &Source, parts: Parts) -> bool {
+    let style = Style::detect(&io::stdout());
+    let mut rendered = true;
+
+    // The days whose input could not be read were reported with the records.
+    for (solver, string) in solvers.iter().zip(inputs).filter_map(|(solver, input)| Some((solver, input.as_ref()?))) {
+        let day = solver.day();
+        let rendering = solution::render(*solver, string, parts, style).map_err(|error| error.in_file(source.input.name(day)));
+
+        match rendering {
+            Ok(Some(rendering)) => print!("\n{}\n{rendering}", style.paint("1", format!("Day {day}: {}", solver.title()))),
+            Ok(None) => eprintln!("Day {day} has nothing to render"),
+            Err(error) => {
+                rendered = false;
+                eprintln!("Day {day}: {error}");
+            },
+        }
+    }
+
+    rendered
+}
+
+/// Sorts the JSON arrays of a file, one per line, together with the divider
+/// packets, and reports where the dividers ended up.
+fn run_sort_packets(file: Option<&Input>, source: &Source) -> SolveResult<()> {
+    let (string, name) = match file {
+        Some(file) => (file.read(13)?, file.name(13)),
+        None => (source.read(13)?, source.input.name(13)),
+    };
+
+    let packets = parse_json_packets(&string).map_err(|error| error.in_file(name))?;
+    let (sorted, positions) = sort_with_dividers(packets);
+    let style = Style::detect(&io::stdout());
+
+    for (index, packet) in sorted.iter().enumerate() {
+        if positions.contains(&(index + 1)) {
+            println!("{}", style.paint("1;93", packet));
+        } else {
+            println!("{packet}");
+        }
+    }
+
+    println!();
+    for (n, position) in DIVIDERS.into_iter().zip(&positions) {
+        println!("Divider {} is at position {position}", Packet::divider(n));
+    }
+    println!("Decoder key: {}", positions.iter().product::<usize>());
 
-    let solution = SOLVERS[day - 1]();
+    Ok(())
+}
+
+/// Plays rounds of day 11 with exact worry levels, and checks that keeping
+/// them modulo the tests, as part 2 does, routes every item the same way.
+fn run_exact_monkeys(file: Option<&Input>, source: &Source, rounds: usize) -> SolveResult<ExitCode> {
+    let (string, name) = match file {
+        Some(file) => (file.read(11)?, file.name(11)),
+        None => (source.read(11)?, source.input.name(11)),
+    };
+
+    let monkeys = <Day11 as Solution>::parse(&string).map_err(|error| error.in_file(name))?;
+    let exact = exact_throws(&monkeys, rounds)?;
+    let modular = throws(&monkeys, rounds)?;
+
+    println!("After {rounds} rounds with exact worry levels:");
+    for monkey in 0..monkeys.len() {
+        let inspected = exact.iter().filter(|(from, _)| *from == monkey).count();
+        println!("Monkey {monkey} inspected items {inspected} times");
+    }
+    println!();
+
+    let style = Style::detect(&io::stdout());
+
+    match exact.iter().zip(&modular).position(|(exact, modular)| exact != modular) {
+        Some(index) => {
+            let ((from, exact), (_, modular)) = (exact[index], modular[index]);
+            println!(
+                "{} throw {}: monkey {from} throws to monkey {exact} exactly, to monkey {modular} modulo the tests",
+                style.paint("91", "Differs at"),
+                index + 1,
+            );
+            Ok(ExitCode::FAILURE)
+        },
+        None if exact.len() != modular.len() => {
+            println!("{} {} throws exactly, {} modulo the tests", style.paint("91", "Differs:"), exact.len(), modular.len());
+            Ok(ExitCode::FAILURE)
+        },
+        None => {
+            println!("{} all {} throws are routed the same modulo the tests", style.paint("92", "Agrees:"), exact.len());
+            Ok(ExitCode::SUCCESS)
+        },
+    }
+}
+
+fn run_verify(source: &Source, answers: &Answers, jobs: usize) -> ExitCode {
+    let style = Style::detect(&io::stdout());
+    let (mut passed, mut failed) = (0, 0);
+
+    let records = run_parallel(SOLVERS, jobs, |solver| Record::new(solver, solve_day(solver, source, Parts::Both)));
+
+    for record in records {
+        let day = record.day;
+
+        if let Some(error) = &record.error {
+            failed += 1;
+            println!("Day {day:2}: {} {}", style.paint("91", "ERROR"), error.message);
+            continue;
+        }
+
+        let (part1, part2) = check(&record, answers);
+
+        for (part, check) in [(1, part1), (2, part2)] {
+            let status = match &check {
+                Check::Pass => {
+                    passed += 1;
+                    style.paint("92", &check)
+                },
+                Check::Fail { .. } => {
+                    failed += 1;
+                    style.paint("91", &check)
+                },
+                Check::Unknown | Check::Unsolved => style.paint("2", &check),
+            };
+
+            println!("Day {day:2} part {part}: {status}");
+        }
+    }
+
+    println!("\n{passed} passed, {failed} failed");
+
+    if failed > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Benchmarks the solvers and prints their statistics. Returns the results
+/// and whether any step regressed against the baseline.
+fn run_bench(solvers: &[&dyn Solver], source: &Source, options: Options, baseline: Option<&Baseline>, threshold: f64) -> (Baseline, bool) {
+    let style = Style::detect(&io::stdout());
+    let mut results = Baseline::new();
+    let mut regressed = false;
+
+    println!(
+        "Benchmarking with {} warmup and {} measured iterations (µs)",
+        options.warmup, options.iterations
+    );
+    print!("{}", style.paint("1", "Day | Step  |        Min |     Median |        P95 |     Stddev"));
+    println!("{}", if baseline.is_some() { style.paint("1", " |   Change") } else { String::new() });
+
+    for solver in solvers {
+        let day = solver.day();
+        let result = source
+            .read(day)
+            .and_then(|string| bench::bench(*solver, &string, options))
+            .map_err(|error| error.in_file(source.input.name(day)));
+
+        let result = match result {
+            Ok(result) => result,
+            Err(error) => {
+                eprintln!("{}: {error}", Style::detect(&io::stderr()).paint("91", format!("Error on Day {day:2}")));
+                continue;
+            },
+        };
+
+        for (step, stats) in result.steps() {
+            print!(
+                "{day:<3} | {step:5} | {:10.1} | {:10.1} | {:10.1} | {:10.1}",
+                stats.min, stats.median, stats.p95, stats.stddev
+            );
+
+            let previous = baseline
+                .and_then(|baseline| baseline.get(&day))
+                .and_then(|previous| previous.steps().into_iter().find(|(name, _)| *name == step))
+                .map(|(_, previous)| *previous);
+
+            match previous {
+                Some(previous) if is_regression(stats, &previous, threshold) => {
+                    regressed = true;
+                    println!(" | {} {}", style.paint("91", format!("{:+7.1}%", change(stats, &previous))), style.paint("91", "REGRESSION"));
+                },
+                Some(previous) => println!(" | {:+7.1}%", change(stats, &previous)),
+                None if baseline.is_some() => println!(" |        -"),
+                None => println!(),
+            }
+        }
+
+        results.insert(day, result);
+    }
+
+    if regressed {
+        println!("\nSome steps regressed by more than {threshold}% against the baseline");
+    }
+
+    (results, regressed)
+}
+
+/// Downloads the inputs of the given days, or of every day, into the input
+/// directory. Inputs that are already there are kept unless `--force` is set.
+fn run_fetch(args: &mut Vec<String>, input: &Input, example: bool, base_url: Option<String>) -> Result<ExitCode, String> {
+    let force = take_flag(args, "--force");
+
+    let dir = match input {
+        _ if example => return Err(String::from("fetch cannot be combined with --example")),
+        Input::Path(dir) if !dir.is_file() => dir,
+        _ => return Err(format!("The input of fetch must be a directory (received {input})")),
+    };
+
+    let solvers = match args.get(2) {
+        Some(days) => parse_days(days)?,
+        None => SOLVERS.to_vec(),
+    };
+
+    let client = Client::from_env(base_url).map_err(|error| error.to_string())?;
 
-    let elapsed = start.elapsed().as_micros();
-    let time = (elapsed as f64) / 1000.0;
-    
-    match solution {
-        Ok((part1, part2)) => println!("{part1:12} | {part2:12} | {time:07.3}"),
-        Err(error) => eprintln!("\x1b[91mError on Day {day:2}\x1b[0m: {error}"),
+    let mut failed = false;
+
+    for solver in solvers {
+        let day = solver.day();
+
+        match client.fetch(dir, day, force) {
+            Ok((path, true)) => println!("Day {day:2}: downloaded {}", path.display()),
+            Ok((path, false)) => println!("Day {day:2}: already cached in {}", path.display()),
+            // Later days would fail the same way, so stop asking.
+            Err(error @ (ClientError::Unauthorized | ClientError::RateLimited(_))) => {
+                return Err(format!("Failed to download day {day} from {}: {error}", client.base_url()));
+            },
+            Err(error) => {
+                failed = true;
+                eprintln!("Day {day:2}: failed to download from {}: {error}", client.base_url());
+            },
+        }
+    }
+
+    if failed {
+        Ok(ExitCode::FAILURE)
+    } else {
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+/// Solves one part of a day and submits the answer, unless the history of
+/// earlier attempts already tells how the server would judge it. An answer
+/// outside the bounds of earlier ones is submitted with a warning. Every
+/// attempt is recorded in the history.
+fn run_submit(args: &mut Vec<String>, source: &Source, client: &Client) -> Result<ExitCode, String> {
+    let history_path = take_option(args, "--history")?.map(PathBuf::from).unwrap_or_else(|| match &source.input {
+        Input::Path(dir) if dir.is_dir() => dir.join(HISTORY_FILE),
+        _ => PathBuf::from(HISTORY_FILE),
+    });
+
+    let (Some(day), Some(part)) = (args.get(2), args.get(3)) else {
+        return Err(String::from("A day and a part must be specified."));
+    };
+
+    let solver = day
+        .parse()
+        .ok()
+        .and_then(solver)
+        .ok_or_else(|| format!("No solution is registered for day {day} (available: {})", available_days()))?;
+    let (part, parts) = match part.as_str() {
+        "1" => (1, Parts::One),
+        "2" => (2, Parts::Two),
+        rec => return Err(format!("{rec} is not a valid part. Available parts are: 1, 2.")),
+    };
+    let day = solver.day();
+
+    let report = solve_day(solver, source, parts).map_err(|error| format!("Failed to solve day {day}: {error}"))?;
+    let answer = report.part1.or(report.part2).unwrap_or_default();
+    if answer.is_empty() {
+        return Err(format!("Day {day} part {part} has no answer to submit"));
     }
-}
\ No newline at end of file
+
+    let mut history = History::load(&history_path)
+        .map_err(|error| format!("Failed to read the history from {}: {error}", history_path.display()))?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs());
+
+    match history.check(day, part, &answer, now) {
+        Verdict::Submit => (),
+        Verdict::Solved(solved) if solved == answer => {
+            println!("Day {day} part {part}: {answer} was already accepted");
+            return Ok(ExitCode::SUCCESS);
+        },
+        // The bounds come from earlier answers that may have been wrong for
+        // other reasons, so they only warn.
+        Verdict::OutOfBounds(reason) => eprintln!("Warning: {reason}"),
+        verdict => return Err(format!("Not submitting {answer} for day {day} part {part}: {verdict}")),
+    }
+
+    let outcome = match client.submit(day, part, &answer) {
+        Ok(outcome) => outcome,
+        Err(ClientError::RateLimited(seconds)) => Outcome::RateLimited(seconds),
+        Err(error) => return Err(format!("Failed to submit to {}: {error}", client.base_url())),
+    };
+
+    history.record(day, part, &answer, outcome.clone());
+    history
+        .save(&history_path)
+        .map_err(|error| format!("Failed to save the history to {}: {error}", history_path.display()))?;
+
+    println!("Day {day} part {part}: {answer} is {outcome}");
+
+    match outcome {
+        Outcome::Correct | Outcome::AlreadySolved => Ok(ExitCode::SUCCESS),
+        _ => Ok(ExitCode::FAILURE),
+    }
+}
+
+/// Re-runs a day whenever its input or its source changes, rebuilding the
+/// binary first in the latter case. Each run is made by a fresh process, so
+/// that it picks up the rebuilt solver.
+fn run_watch(args: &mut Vec<String>, input: &Input) -> Result<ExitCode, String> {
+    let part = take_option(args, "--part")?.unwrap_or_else(|| String::from("both"));
+    part.parse::<Parts>()?;
+    let interval = Duration::from_millis(take_parsed(args, "--interval")?.unwrap_or(500));
+
+    let Some(day) = args.get(2) else {
+        return Err(String::from("A day must be specified."));
+    };
+    let solver = day
+        .parse()
+        .ok()
+        .and_then(solver)
+        .ok_or_else(|| format!("No solution is registered for day {day} (available: {})", available_days()))?;
+    let day = solver.day();
+
+    let Some(input_path) = input.path(day) else {
+        return Err(String::from("watch cannot read its input from stdin"));
+    };
+
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let source_path = manifest_dir.join("src").join(format!("day{day}.rs"));
+    let exe = env::current_exe().map_err(|error| format!("Failed to locate the running binary: {error}"))?;
+
+    let mut watcher = Watcher::new(vec![source_path.clone(), input_path.clone()]);
+    let mut previous: Option<Run> = None;
+    let mut rebuild = false;
+
+    println!("Watching {} and {} (Ctrl-C to stop)", source_path.display(), input_path.display());
+
+    loop {
+        if !rebuild || build(manifest_dir) {
+            let output = Command::new(&exe)
+                .args(["--input".as_ref(), input_path.as_os_str()])
+                .args(["--format", "json", "day", &day.to_string(), "--part", &part])
+                .output()
+                .map_err(|error| format!("Failed to run {}: {error}", exe.display()))?;
+
+            let runs = parse_runs(&String::from_utf8_lossy(&output.stdout));
+            match runs.as_deref() {
+                Ok([run]) => {
+                    println!("\n{}", Style::detect(&io::stdout()).paint("1", format!("Day {day}: {}", solver.title())));
+                    diff(previous.as_ref(), run).iter().for_each(|line| println!("{line}"));
+                    previous = Some(run.clone());
+                },
+                _ => eprintln!("Failed to run day {day}: {}", String::from_utf8_lossy(&output.stderr).trim_end()),
+            }
+        }
+
+        let changed = loop {
+            thread::sleep(interval);
+
+            let changed = watcher.changed();
+            if !changed.is_empty() {
+                break changed;
+            }
+        };
+
+        rebuild = changed.contains(&source_path);
+    }
+}
+
+/// Rebuilds the binary with the profile it was built with, printing the
+/// compiler errors. Returns whether the build succeeded.
+fn build(manifest_dir: &Path) -> bool {
+    println!("\nRebuilding...");
+
+    let mut command = Command::new(env::var_os("CARGO").unwrap_or_else(|| "cargo".into()));
+    command.args(["build", "--quiet", "--manifest-path"]).arg(manifest_dir.join("Cargo.toml"));
+    if !cfg!(debug_assertions) {
+        command.arg("--release");
+    }
+
+    match command.status() {
+        Ok(status) => status.success(),
+        Err(error) => {
+            eprintln!("Failed to run cargo: {error}");
+            false
+        },
+    }
+}
@@ -0,0 +1,317 @@
+use std::env;
+use std::fmt::Display;
+use std::io::{self, IsTerminal, Write};
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::solution::{Report, SolveResult, Solver};
+use crate::utils::SolveError;
+
+/// How the results of a run are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+  #[default]
+  Table,
+  Json,
+  Csv,
+}
+
+impl FromStr for Format {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "table" => Ok(Self::Table),
+      "json" => Ok(Self::Json),
+      "csv" => Ok(Self::Csv),
+      rec => Err(format!("{rec} is not a valid format. Available formats are: table, json, csv.")),
+    }
+  }
+}
+
+/// ANSI styling of the output, disabled when the stream it is written to is
+/// not a terminal or `NO_COLOR` is set. Stdout and stderr are detected
+/// separately, as either may be redirected alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Style {
+  colour: bool,
+}
+
+impl Style {
+  pub fn new(colour: bool) -> Self {
+    Self { colour }
+  }
+
+  /// The style for writing to `stream`, such as `io::stdout()`.
+  pub fn detect(stream: &impl IsTerminal) -> Self {
+    Self::new(stream.is_terminal() && env::var_os("NO_COLOR").is_none())
+  }
+
+  pub fn paint(&self, code: &str, text: impl Display) -> String {
+    if self.colour {
+      format!("\x1b[{code}m{text}\x1b[0m")
+    } else {
+      text.to_string()
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErrorRecord {
+  pub kind: &'static str,
+  pub message: String,
+}
+
+/// The outcome of a single day, flattened for printing. Timings are in
+/// microseconds.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Record {
+  pub day: usize,
+  pub title: &'static str,
+  pub part1: Option<String>,
+  pub part2: Option<String>,
+  pub parse_us: Option<u64>,
+  pub part1_us: Option<u64>,
+  pub part2_us: Option<u64>,
+  pub total_us: Option<u64>,
+  pub error: Option<ErrorRecord>,
+}
+
+fn micros(duration: Duration) -> u64 {
+  duration.as_micros() as u64
+}
+
+impl Record {
+  pub fn new(solver: &dyn Solver, result: SolveResult<Report>) -> Self {
+    let mut record = Self {
+      day: solver.day(),
+      title: solver.title(),
+      part1: None,
+      part2: None,
+      parse_us: None,
+      part1_us: None,
+      part2_us: None,
+      total_us: None,
+      error: None,
+    };
+
+    match result {
+      Ok(report) => {
+        record.parse_us = Some(micros(report.parse_time));
+        record.part1_us = report.part1.as_ref().map(|_| micros(report.part1_time));
+        record.part2_us = report.part2.as_ref().map(|_| micros(report.part2_time));
+        record.total_us = Some(micros(report.total_time()));
+        record.part1 = report.part1;
+        record.part2 = report.part2;
+      },
+      Err(error) => {
+        let kind = match error {
+          SolveError::Io { .. } => "io",
+          SolveError::Parse { .. } => "parse",
+          SolveError::Logic(_) => "logic",
+          SolveError::Panic { .. } => "panic",
+        };
+
+        record.error = Some(ErrorRecord { kind, message: error.to_string() });
+      },
+    }
+
+    record
+  }
+}
+
+/// `total_us` adds up the time spent on each day, while `wall_us` is the
+/// elapsed time of the whole run, which is shorter when days run in parallel.
+#[derive(Serialize)]
+struct Summary<'a> {
+  days: &'a [Record],
+  total_us: u64,
+  wall_us: u64,
+}
+
+fn total_us(records: &[Record]) -> u64 {
+  records.iter().filter_map(|record| record.total_us).sum()
+}
+
+/// Prints the records in `format`. Only the table writes to `err`, which
+/// receives the errors of the failed days painted with `err_style`.
+pub fn write_records(out: &mut impl Write, err: &mut impl Write, format: Format, style: Style, err_style: Style, records: &[Record], wall: Duration) -> io::Result<()> {
+  match format {
+    Format::Table => write_table(out, err, style, err_style, records, wall),
+    Format::Json => write_json(out, records, wall),
+    Format::Csv => write_csv(out, records),
+  }
+}
+
+fn write_table(out: &mut impl Write, err: &mut impl Write, style: Style, err_style: Style, records: &[Record], wall: Duration) -> io::Result<()> {
+  writeln!(
+    out,
+    "{} | {}       | {}        | Parse   | Part 1  | Part 2  | Time (ms)",
+    style.paint("1", "Day"),
+    style.paint("94", "Part 1"),
+    style.paint("93", "Part2"),
+  )?;
+
+  for record in records {
+    let day = record.day;
+
+    if let Some(error) = &record.error {
+      writeln!(err, "{}: {}", err_style.paint("91", format!("Error on Day {day:2}")), error.message)?;
+      continue;
+    }
+
+    // Parts that were not solved have no timing and are left blank.
+    let [parse, time1, time2, time] = [record.parse_us, record.part1_us, record.part2_us, record.total_us]
+      .map(|us| us.map(|us| format!("{:07.3}", (us as f64) / 1000.0)).unwrap_or_else(|| " ".repeat(7)));
+
+    writeln!(
+      out,
+      "{}  | {:12} | {:12} | {parse} | {time1} | {time2} | {time}",
+      style.paint("1", format!("{day:<2}")),
+      record.part1.as_deref().unwrap_or_default(),
+      record.part2.as_deref().unwrap_or_default(),
+    )?;
+  }
+
+  let total = (total_us(records) as f64) / 1000.0;
+  let wall = (micros(wall) as f64) / 1000.0;
+
+  writeln!(out, "\nTotal Time Elapsed: {wall:.3} ms (sum of the days: {total:.3} ms)")
+}
+
+fn write_json(out: &mut impl Write, records: &[Record], wall: Duration) -> io::Result<()> {
+  let summary = Summary { days: records, total_us: total_us(records), wall_us: micros(wall) };
+  serde_json::to_writer_pretty(&mut *out, &summary)?;
+  writeln!(out)
+}
+
+fn csv_field(field: &str) -> String {
+  if field.contains([',', '"', '\n', '\r']) {
+    format!("\"{}\"", field.replace('"', "\"\""))
+  } else {
+    field.to_string()
+  }
+}
+
+fn write_csv(out: &mut impl Write, records: &[Record]) -> io::Result<()> {
+  writeln!(out, "day,title,part1,part2,parse_us,part1_us,part2_us,total_us,error_kind,error_message")?;
+
+  for record in records {
+    let number = |us: Option<u64>| us.map(|us| us.to_string()).unwrap_or_default();
+
+    let fields = [
+      record.day.to_string(),
+      csv_field(record.title),
+      csv_field(record.part1.as_deref().unwrap_or_default()),
+      csv_field(record.part2.as_deref().unwrap_or_default()),
+      number(record.parse_us),
+      number(record.part1_us),
+      number(record.part2_us),
+      number(record.total_us),
+      record.error.as_ref().map(|error| error.kind).unwrap_or_default().to_string(),
+      csv_field(record.error.as_ref().map(|error| error.message.as_str()).unwrap_or_default()),
+    ];
+
+    writeln!(out, "{}", fields.join(","))?;
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use super::{csv_field, write_records, ErrorRecord, Format, Record, Style};
+
+  fn records() -> Vec<Record> {
+    vec![
+      Record {
+        day: 1,
+        title: "Calorie Counting",
+        part1: Some(String::from("24000")),
+        part2: Some(String::from("45000")),
+        parse_us: Some(10),
+        part1_us: Some(2),
+        part2_us: Some(3),
+        total_us: Some(15),
+        error: None,
+      },
+      Record {
+        day: 2,
+        title: "Rock Paper Scissors",
+        part1: None,
+        part2: None,
+        parse_us: None,
+        part1_us: None,
+        part2_us: None,
+        total_us: None,
+        error: Some(ErrorRecord { kind: "io", message: String::from("No such file, or directory") }),
+      },
+    ]
+  }
+
+  fn render(format: Format, style: Style, err_style: Style) -> (String, String) {
+    let (mut out, mut err) = (Vec::new(), Vec::new());
+    write_records(&mut out, &mut err, format, style, err_style, &records(), Duration::from_micros(12)).unwrap();
+    (String::from_utf8(out).unwrap(), String::from_utf8(err).unwrap())
+  }
+
+  #[test]
+  fn test_format_from_str() {
+    assert_eq!("json".parse(), Ok(Format::Json));
+    assert_eq!("csv".parse(), Ok(Format::Csv));
+    assert_eq!("table".parse(), Ok(Format::Table));
+    assert!("yaml".parse::<Format>().is_err());
+  }
+
+  #[test]
+  fn test_write_json() {
+    let (out, err) = render(Format::Json, Style::new(false), Style::new(false));
+    let value: serde_json::Value = serde_json::from_str(&out).unwrap();
+
+    assert_eq!(value["total_us"], 15);
+    assert_eq!(value["wall_us"], 12);
+    assert_eq!(value["days"][0]["part1"], "24000");
+    assert_eq!(value["days"][0]["error"], serde_json::Value::Null);
+    assert_eq!(value["days"][1]["error"]["kind"], "io");
+    assert!(err.is_empty());
+  }
+
+  #[test]
+  fn test_write_csv() {
+    let (out, _) = render(Format::Csv, Style::new(false), Style::new(false));
+    let lines = out.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines[1], "1,Calorie Counting,24000,45000,10,2,3,15,,");
+    assert_eq!(lines[2], "2,Rock Paper Scissors,,,,,,,io,\"No such file, or directory\"");
+  }
+
+  #[test]
+  fn test_write_table() {
+    let (out, err) = render(Format::Table, Style::new(false), Style::new(false));
+    assert!(!out.contains('\x1b'));
+    assert!(out.contains("1   | 24000        | 45000        | 000.010 | 000.002 | 000.003 | 000.015"));
+    assert!(out.ends_with("Total Time Elapsed: 0.012 ms (sum of the days: 0.015 ms)\n"));
+    assert_eq!(err, "Error on Day  2: No such file, or directory\n");
+
+    // Each stream is painted only when its own style says so.
+    let (out, err) = render(Format::Table, Style::new(true), Style::new(false));
+    assert!(out.starts_with("\x1b[1mDay\x1b[0m"));
+    assert!(!err.contains('\x1b'));
+
+    let (out, err) = render(Format::Table, Style::new(false), Style::new(true));
+    assert!(!out.contains('\x1b'));
+    assert!(err.starts_with("\x1b[91mError on Day  2\x1b[0m"));
+  }
+
+  #[test]
+  fn test_csv_field() {
+    assert_eq!(csv_field("abc"), "abc");
+    assert_eq!(csv_field("a,b"), "\"a,b\"");
+    assert_eq!(csv_field("a\"b"), "\"a\"\"b\"");
+    assert_eq!(csv_field("a\nb"), "\"a\nb\"");
+  }
+}
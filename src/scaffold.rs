@@ -0,0 +1,146 @@
+use std::error::Error;
+use std::fs::{self, read_to_string, write};
+use std::path::{Path, PathBuf};
+
+use crate::utils::input_file;
+
+const TEMPLATE: &str = r#"use crate::solution::{Solution, SolveResult};
+use crate::utils::{input_lines, SolveError};
+
+pub struct Day{DAY};
+
+impl Solution for Day{DAY} {
+  type Parsed = Vec<String>;
+
+  const DAY: usize = {DAY};
+  const TITLE: &'static str = "{TITLE}";
+
+  fn parse(input: &str) -> SolveResult<Self::Parsed> {
+    Ok(input_lines(input))
+  }
+
+  fn part1(_lines: &Self::Parsed) -> SolveResult<String> {
+    Err(SolveError::logic("Part 1 is not solved yet"))
+  }
+
+  fn part2(_lines: &Self::Parsed) -> SolveResult<String> {
+    Err(SolveError::logic("Part 2 is not solved yet"))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Day{DAY};
+  use crate::solution::Solution;
+
+  // TODO: paste the sample of the puzzle, also into input/examples/day{DAY}.txt.
+  const SAMPLE: &str = "";
+
+  #[test]
+  fn test_parse() {
+    assert_eq!(Day{DAY}::parse(SAMPLE).unwrap().len(), SAMPLE.lines().count());
+  }
+}
+"#;
+
+/// The source of a new day module, with unsolved parts.
+pub fn template(day: usize, title: &str) -> String {
+  TEMPLATE.replace("{DAY}", &day.to_string()).replace("{TITLE}", &title.replace('"', "\\\""))
+}
+
+/// Adds `dayN::DayN` to the `days!` list of `lib_source`, keeping the list
+/// sorted by day and laid out four entries to a line.
+pub fn register(lib_source: &str, day: usize) -> Result<String, String> {
+  let start = lib_source.find("days! {\n").ok_or("Failed to find the days! list")? + "days! {\n".len();
+  let end = start + lib_source[start..].find("\n}").ok_or("The days! list is not closed")?;
+
+  let mut entries = lib_source[start..end]
+    .split(',')
+    .map(str::trim)
+    .filter(|entry| !entry.is_empty())
+    .map(|entry| {
+      let day = entry
+        .strip_prefix("day")
+        .and_then(|entry| entry.split_once("::"))
+        .and_then(|(day, _)| day.parse::<usize>().ok())
+        .ok_or_else(|| format!("Unexpected entry in the days! list: {entry}"))?;
+
+      Ok((day, entry.to_string()))
+    })
+    .collect::<Result<Vec<_>, String>>()?;
+
+  if entries.iter().any(|(registered, _)| *registered == day) {
+    return Err(format!("Day {day} is already registered"));
+  }
+
+  entries.push((day, format!("day{day}::Day{day}")));
+  entries.sort();
+
+  let lines = entries
+    .chunks(4)
+    .map(|row| {
+      let row = row.iter().map(|(_, entry)| format!("{:<16}", format!("{entry},"))).collect::<String>();
+      format!("  {}", row.trim_end())
+    })
+    .collect::<Vec<_>>();
+
+  Ok(format!("{}{}{}", &lib_source[..start], lines.join("\n"), &lib_source[end..]))
+}
+
+/// Creates the module of `day` in the crate at `root`, registers it, and adds
+/// empty input and example files. Returns the files created or changed.
+pub fn scaffold(root: &Path, day: usize, title: &str) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+  let module = root.join("src").join(format!("day{day}.rs"));
+  if module.exists() {
+    return Err(format!("{} already exists", module.display()).into());
+  }
+
+  let lib = root.join("src").join("lib.rs");
+  let registered = register(&read_to_string(&lib)?, day)?;
+
+  write(&module, template(day, title))?;
+  write(&lib, registered)?;
+  let mut changed = vec![module, lib];
+
+  for dir in [root.join("input"), root.join("input").join("examples")] {
+    let path = dir.join(input_file(day));
+
+    if !path.exists() {
+      fs::create_dir_all(&dir)?;
+      write(&path, "")?;
+      changed.push(path);
+    }
+  }
+
+  Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{register, template};
+
+  #[test]
+  fn test_register() {
+    let lib = "days! {\n  day1::Day1,     day2::Day2,     day3::Day3,     day4::Day4,\n  day5::Day5,\n}\n";
+
+    assert_eq!(
+      register(lib, 6).unwrap(),
+      "days! {\n  day1::Day1,     day2::Day2,     day3::Day3,     day4::Day4,\n  day5::Day5,     day6::Day6,\n}\n"
+    );
+    assert_eq!(
+      register("days! {\n  day2::Day2,\n}\n", 1).unwrap(),
+      "days! {\n  day1::Day1,     day2::Day2,\n}\n"
+    );
+    assert!(register(lib, 5).is_err());
+    assert!(register("fn main() {}", 6).is_err());
+  }
+
+  #[test]
+  fn test_template() {
+    let source = template(14, "Regolith \"Reservoir\"");
+    assert!(source.contains("pub struct Day14;"));
+    assert!(source.contains("const DAY: usize = 14;"));
+    assert!(source.contains(r#"const TITLE: &'static str = "Regolith \"Reservoir\"";"#));
+    assert!(!source.contains("{DAY}"));
+  }
+}
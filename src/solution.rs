@@ -0,0 +1,380 @@
+use std::any::Any;
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::cell::{Cell, RefCell};
+use std::panic::{self, resume_unwind, AssertUnwindSafe};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Once;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::output::Style;
+use crate::utils::SolveError;
+
+pub type SolveResult<T> = Result<T, SolveError>;
+
+/// A puzzle solution, split into parsing the input and solving each part on
+/// the parsed representation.
+pub trait Solution {
+  type Parsed: 'static;
+
+  const DAY: usize;
+  const TITLE: &'static str;
+
+  fn parse(input: &str) -> SolveResult<Self::Parsed>;
+  fn part1(parsed: &Self::Parsed) -> SolveResult<String>;
+  fn part2(parsed: &Self::Parsed) -> SolveResult<String>;
+
+  /// Draws how the answers of `parts` are found, for days that can show it.
+  fn render(_parsed: &Self::Parsed, _parts: Parts, _style: Style) -> SolveResult<Option<String>> {
+    Ok(None)
+  }
+}
+
+/// Object safe view of a [`Solution`], so that solutions with different
+/// parsed types can be stored side by side in the registry.
+pub trait Solver: Sync {
+  fn day(&self) -> usize;
+  fn title(&self) -> &'static str;
+  fn parse(&self, input: &str) -> SolveResult<Box<dyn Any>>;
+  fn part1(&self, parsed: &dyn Any) -> SolveResult<String>;
+  fn part2(&self, parsed: &dyn Any) -> SolveResult<String>;
+  fn render(&self, parsed: &dyn Any, parts: Parts, style: Style) -> SolveResult<Option<String>>;
+}
+
+impl<S: Solution + Sync> Solver for S {
+  fn day(&self) -> usize {
+    S::DAY
+  }
+
+  fn title(&self) -> &'static str {
+    S::TITLE
+  }
+
+  fn parse(&self, input: &str) -> SolveResult<Box<dyn Any>> {
+    Ok(Box::new(<S as Solution>::parse(input)?))
+  }
+
+  fn part1(&self, parsed: &dyn Any) -> SolveResult<String> {
+    <S as Solution>::part1(downcast::<S>(parsed))
+  }
+
+  fn part2(&self, parsed: &dyn Any) -> SolveResult<String> {
+    <S as Solution>::part2(downcast::<S>(parsed))
+  }
+
+  fn render(&self, parsed: &dyn Any, parts: Parts, style: Style) -> SolveResult<Option<String>> {
+    <S as Solution>::render(downcast::<S>(parsed), parts, style)
+  }
+}
+
+fn downcast<S: Solution>(parsed: &dyn Any) -> &S::Parsed {
+  parsed
+    .downcast_ref()
+    .unwrap_or_else(|| panic!("Day {} was handed the parsed input of another day", S::DAY))
+}
+
+/// Which parts of a day to solve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Parts {
+  One,
+  Two,
+  #[default]
+  Both,
+}
+
+impl Parts {
+  pub fn part1(self) -> bool {
+    self != Self::Two
+  }
+
+  pub fn part2(self) -> bool {
+    self != Self::One
+  }
+}
+
+impl FromStr for Parts {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "1" => Ok(Self::One),
+      "2" => Ok(Self::Two),
+      "both" => Ok(Self::Both),
+      rec => Err(format!("{rec} is not a valid part. Available parts are: 1, 2, both.")),
+    }
+  }
+}
+
+/// The answers of a day together with the time spent in each step. A part
+/// that was not solved has no answer and took no time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+  pub day: usize,
+  pub part1: Option<String>,
+  pub part2: Option<String>,
+  pub parse_time: Duration,
+  pub part1_time: Duration,
+  pub part2_time: Duration,
+}
+
+impl Report {
+  pub fn total_time(&self) -> Duration {
+    self.parse_time + self.part1_time + self.part2_time
+  }
+}
+
+/// Solves both parts of a day, timing each step. A panicking solver is
+/// reported as a [`SolveError::Panic`].
+pub fn run(solver: &dyn Solver, input: &str) -> SolveResult<Report> {
+  run_parts(solver, input, Parts::Both)
+}
+
+/// Like [`run`], skipping the part that is not in `parts`.
+pub fn run_parts(solver: &dyn Solver, input: &str, parts: Parts) -> SolveResult<Report> {
+  catch_panic(|| run_steps(solver, input, parts))
+}
+
+/// Parses the input and renders the parts in `parts`, `None` when the day
+/// has no rendering.
+pub fn render(solver: &dyn Solver, input: &str, parts: Parts, style: Style) -> SolveResult<Option<String>> {
+  catch_panic(|| {
+    let parsed = solver.parse(input)?;
+    solver.render(parsed.as_ref(), parts, style)
+  })
+}
+
+fn time<T>(f: impl FnOnce() -> SolveResult<T>) -> SolveResult<(T, Duration)> {
+  let start = Instant::now();
+  let value = f()?;
+  Ok((value, start.elapsed()))
+}
+
+fn run_steps(solver: &dyn Solver, input: &str, parts: Parts) -> SolveResult<Report> {
+  let (parsed, parse_time) = time(|| solver.parse(input))?;
+
+  let (part1, part1_time) = if parts.part1() {
+    let (answer, time) = time(|| solver.part1(parsed.as_ref()))?;
+    (Some(answer), time)
+  } else {
+    (None, Duration::ZERO)
+  };
+
+  let (part2, part2_time) = if parts.part2() {
+    let (answer, time) = time(|| solver.part2(parsed.as_ref()))?;
+    (Some(answer), time)
+  } else {
+    (None, Duration::ZERO)
+  };
+
+  Ok(Report {
+    day: solver.day(),
+    part1,
+    part2,
+    parse_time,
+    part1_time,
+    part2_time,
+  })
+}
+
+thread_local! {
+  static CATCHING: Cell<bool> = const { Cell::new(false) };
+  static CAUGHT: RefCell<(Option<String>, Option<String>)> = const { RefCell::new((None, None)) };
+}
+
+/// Runs `f`, turning a panic into a [`SolveError::Panic`] instead of
+/// unwinding further. Caught panics are not printed by the panic hook; their
+/// backtrace is kept when enabled through `RUST_BACKTRACE`.
+pub fn catch_panic<T>(f: impl FnOnce() -> SolveResult<T>) -> SolveResult<T> {
+  static HOOK: Once = Once::new();
+
+  HOOK.call_once(|| {
+    let previous = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+      if !CATCHING.get() {
+        return previous(info);
+      }
+
+      let location = info.location().map(ToString::to_string);
+      let backtrace = Some(Backtrace::capture())
+        .filter(|backtrace| backtrace.status() == BacktraceStatus::Captured)
+        .map(|backtrace| backtrace.to_string());
+
+      CAUGHT.set((location, backtrace));
+    }));
+  });
+
+  let catching = CATCHING.replace(true);
+  let result = panic::catch_unwind(AssertUnwindSafe(f));
+  CATCHING.set(catching);
+
+  result.unwrap_or_else(|payload| {
+    let (location, backtrace) = CAUGHT.take();
+    Err(SolveError::Panic { message: panic_message(payload.as_ref()), location, backtrace })
+  })
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+  if let Some(message) = payload.downcast_ref::<&str>() {
+    message.to_string()
+  } else if let Some(message) = payload.downcast_ref::<String>() {
+    message.clone()
+  } else {
+    String::from("Box<dyn Any>")
+  }
+}
+
+/// Applies `f` to every solver on up to `jobs` worker threads. The results
+/// keep the order of `solvers`, whichever day finishes first.
+pub fn run_parallel<T: Send>(solvers: &[&dyn Solver], jobs: usize, f: impl Fn(&dyn Solver) -> T + Sync) -> Vec<T> {
+  let next = AtomicUsize::new(0);
+
+  let mut results = thread::scope(|scope| {
+    let workers = (0..jobs.clamp(1, solvers.len().max(1)))
+      .map(|_| scope.spawn(|| {
+        let mut results = Vec::new();
+
+        loop {
+          let index = next.fetch_add(1, Ordering::Relaxed);
+
+          let Some(solver) = solvers.get(index) else {
+            return results;
+          };
+
+          results.push((index, f(*solver)));
+        }
+      }))
+      .collect::<Vec<_>>();
+
+    workers
+      .into_iter()
+      .flat_map(|worker| worker.join().unwrap_or_else(|payload| resume_unwind(payload)))
+      .collect::<Vec<_>>()
+  });
+
+  results.sort_by_key(|(index, _)| *index);
+  results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{run, run_parallel, run_parts, Parts, Solution, SolveResult, Solver};
+  use crate::utils::{parse_lines, parse_number, SolveError};
+
+  struct Sum;
+
+  impl Solution for Sum {
+    type Parsed = Vec<u32>;
+
+    const DAY: usize = 42;
+    const TITLE: &'static str = "Sum";
+
+    fn parse(input: &str) -> SolveResult<Self::Parsed> {
+      parse_lines(input, |line| parse_number(line, line))
+    }
+
+    fn part1(parsed: &Self::Parsed) -> SolveResult<String> {
+      Ok(parsed.iter().sum::<u32>().to_string())
+    }
+
+    fn part2(parsed: &Self::Parsed) -> SolveResult<String> {
+      Ok(parsed.iter().product::<u32>().to_string())
+    }
+  }
+
+  #[test]
+  fn test_run() {
+    let solver: &dyn Solver = &Sum;
+    assert_eq!(solver.day(), 42);
+    assert_eq!(solver.title(), "Sum");
+
+    let report = run(solver, "2\n3\n4").unwrap();
+    assert_eq!((report.day, report.part1.as_deref(), report.part2.as_deref()), (42, Some("9"), Some("24")));
+
+    assert!(run(solver, "2\nx").is_err());
+  }
+
+  struct Panics;
+
+  impl Solution for Panics {
+    type Parsed = Vec<u32>;
+
+    const DAY: usize = 43;
+    const TITLE: &'static str = "Panics";
+
+    fn parse(input: &str) -> SolveResult<Self::Parsed> {
+      <Sum as Solution>::parse(input)
+    }
+
+    fn part1(parsed: &Self::Parsed) -> SolveResult<String> {
+      Ok(parsed[0].to_string())
+    }
+
+    fn part2(_parsed: &Self::Parsed) -> SolveResult<String> {
+      panic!("Part 2 of day {} is not solved", Self::DAY)
+    }
+  }
+
+  #[test]
+  fn test_run_parts() {
+    assert_eq!("1".parse(), Ok(Parts::One));
+    assert_eq!("both".parse(), Ok(Parts::Both));
+    assert!("3".parse::<Parts>().is_err());
+
+    let report = run_parts(&Sum, "2\n3\n4", Parts::One).unwrap();
+    assert_eq!((report.part1.as_deref(), report.part2), (Some("9"), None));
+
+    // Part 1 of `Panics` would panic on an empty input, so it must not run.
+    let report = run_parts(&Panics, "", Parts::Two);
+    assert!(matches!(report, Err(SolveError::Panic { message, .. }) if message.contains("not solved")));
+  }
+
+  #[test]
+  fn test_run_catches_panics() {
+    let error = run(&Panics, "").unwrap_err();
+    assert!(matches!(&error, SolveError::Panic { message, .. } if message.contains("index out of bounds")));
+
+    let error = run(&Panics, "1").unwrap_err();
+    assert!(matches!(&error, SolveError::Panic { message, .. } if message == "Part 2 of day 43 is not solved"));
+    assert!(matches!(&error, SolveError::Panic { location: Some(location), .. } if location.starts_with("src/solution.rs:")));
+    assert!(error.to_string().contains(": Part 2 of day 43 is not solved"));
+
+    assert!(run(&Sum, "1").is_ok());
+  }
+
+  struct Day<const N: usize>;
+
+  impl<const N: usize> Solution for Day<N> {
+    type Parsed = ();
+
+    const DAY: usize = N;
+    const TITLE: &'static str = "";
+
+    fn parse(_input: &str) -> SolveResult<Self::Parsed> {
+      Ok(())
+    }
+
+    fn part1(_parsed: &Self::Parsed) -> SolveResult<String> {
+      Ok(N.to_string())
+    }
+
+    fn part2(_parsed: &Self::Parsed) -> SolveResult<String> {
+      Ok(String::new())
+    }
+  }
+
+  #[test]
+  fn test_run_parallel() {
+    let solvers: [&dyn Solver; 5] = [&Day::<1>, &Day::<2>, &Day::<3>, &Day::<4>, &Day::<5>];
+
+    // Earlier days take longer, so that they finish last.
+    let results = run_parallel(&solvers, 3, |solver| {
+      std::thread::sleep(std::time::Duration::from_millis(10 * (6 - solver.day() as u64)));
+      run(solver, "").unwrap().part1.unwrap()
+    });
+
+    assert_eq!(results, ["1", "2", "3", "4", "5"]);
+    assert!(run_parallel(&[], 4, |solver| solver.day()).is_empty());
+  }
+}
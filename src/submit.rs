@@ -0,0 +1,242 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::fs::{read_to_string, write};
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// The file the attempts are recorded in, next to the inputs.
+pub const HISTORY_FILE: &str = "submissions.json";
+
+/// How the server judged a submitted answer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+  Correct,
+  TooHigh,
+  TooLow,
+  /// Wrong, without saying in which direction.
+  Wrong,
+  /// The answer was not checked; the server asks to wait that many seconds.
+  RateLimited(Option<u64>),
+  /// The part was already solved, so the answer was not checked.
+  AlreadySolved,
+  /// A response that none of the other outcomes recognised.
+  Unknown,
+}
+
+impl Outcome {
+  /// Parses the page the server answers a submission with.
+  pub fn parse(page: &str) -> Self {
+    if page.contains("That's the right answer") {
+      Self::Correct
+    } else if page.contains("answer is too high") {
+      Self::TooHigh
+    } else if page.contains("answer is too low") {
+      Self::TooLow
+    } else if page.contains("That's not the right answer") {
+      Self::Wrong
+    } else if page.contains("You gave an answer too recently") {
+      Self::RateLimited(parse_wait(page))
+    } else if page.contains("Did you already complete it") {
+      Self::AlreadySolved
+    } else {
+      Self::Unknown
+    }
+  }
+
+  /// Whether the answer was checked and found wrong.
+  pub fn is_wrong(&self) -> bool {
+    matches!(self, Self::TooHigh | Self::TooLow | Self::Wrong)
+  }
+}
+
+impl Display for Outcome {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::Correct => write!(f, "correct"),
+      Self::TooHigh => write!(f, "wrong, too high"),
+      Self::TooLow => write!(f, "wrong, too low"),
+      Self::Wrong => write!(f, "wrong"),
+      Self::RateLimited(Some(seconds)) => write!(f, "rate limited, retry in {seconds} seconds"),
+      Self::RateLimited(None) => write!(f, "rate limited, retry later"),
+      Self::AlreadySolved => write!(f, "already solved"),
+      Self::Unknown => write!(f, "unrecognised response"),
+    }
+  }
+}
+
+/// The wait in a message such as `You have 1m 34s left to wait.`
+fn parse_wait(page: &str) -> Option<u64> {
+  let (before, _) = page.split_once(" left to wait")?;
+  let wait = &before[before.rfind("You have ")? + "You have ".len()..];
+
+  wait.split_whitespace().try_fold(0, |total, amount| {
+    let (number, unit) = amount.split_at(amount.find(|c: char| !c.is_ascii_digit())?);
+    let seconds = match unit {
+      "h" => 3600,
+      "m" => 60,
+      "s" => 1,
+      _ => return None,
+    };
+
+    Some(total + number.parse::<u64>().ok()? * seconds)
+  })
+}
+
+/// One submitted answer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attempt {
+  pub day: usize,
+  pub part: usize,
+  pub answer: String,
+  pub outcome: Outcome,
+  /// When the answer was submitted, in seconds since the Unix epoch.
+  pub time: u64,
+}
+
+/// What the history says about submitting an answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+  Submit,
+  /// The part was already solved, with this answer.
+  Solved(String),
+  /// The same answer was already submitted and judged wrong.
+  KnownWrong(Outcome),
+  /// The answer is outside the bounds set by earlier wrong answers.
+  OutOfBounds(String),
+  /// The server asked to wait that many more seconds.
+  Wait(u64),
+}
+
+impl Display for Verdict {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::Submit => write!(f, "not submitted yet"),
+      Self::Solved(answer) => write!(f, "already solved with {answer}"),
+      Self::KnownWrong(outcome) => write!(f, "already submitted and judged {outcome}"),
+      Self::OutOfBounds(reason) => write!(f, "{reason}"),
+      Self::Wait(seconds) => write!(f, "the server asked to wait {seconds} more seconds"),
+    }
+  }
+}
+
+/// Every answer submitted so far, oldest first.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct History(Vec<Attempt>);
+
+impl History {
+  /// Reads the history from `path`; a missing file is an empty history.
+  pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+    match read_to_string(path) {
+      Ok(string) => Ok(serde_json::from_str(&string)?),
+      Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+      Err(error) => Err(error.into()),
+    }
+  }
+
+  pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+    write(path, serde_json::to_string_pretty(self)?)?;
+    Ok(())
+  }
+
+  pub fn attempts(&self, day: usize, part: usize) -> impl Iterator<Item = &Attempt> {
+    self.0.iter().filter(move |attempt| (attempt.day, attempt.part) == (day, part))
+  }
+
+  pub fn record(&mut self, day: usize, part: usize, answer: &str, outcome: Outcome) {
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs());
+    self.0.push(Attempt { day, part, answer: answer.to_string(), outcome, time });
+  }
+
+  /// Checks `answer` against the earlier attempts at the part, at `now` in
+  /// seconds since the Unix epoch. Numeric answers must lie strictly between
+  /// the highest answer found too low and the lowest found too high.
+  pub fn check(&self, day: usize, part: usize, answer: &str, now: u64) -> Verdict {
+    let attempts = self.attempts(day, part).collect::<Vec<_>>();
+
+    if let Some(solved) = attempts.iter().find(|attempt| attempt.outcome == Outcome::Correct) {
+      return Verdict::Solved(solved.answer.clone());
+    }
+
+    if let Some(wrong) = attempts.iter().find(|attempt| attempt.answer == answer && attempt.outcome.is_wrong()) {
+      return Verdict::KnownWrong(wrong.outcome.clone());
+    }
+
+    let wait = attempts.last().and_then(|attempt| match attempt.outcome {
+      Outcome::RateLimited(Some(seconds)) => (attempt.time + seconds).checked_sub(now).filter(|&wait| wait > 0),
+      _ => None,
+    });
+
+    if let Some(wait) = wait {
+      return Verdict::Wait(wait);
+    }
+
+    let Ok(value) = answer.trim().parse::<i128>() else {
+      return Verdict::Submit;
+    };
+
+    attempts
+      .iter()
+      .find_map(|attempt| {
+        let bound = attempt.answer.trim().parse::<i128>().ok()?;
+
+        match attempt.outcome {
+          Outcome::TooHigh if value >= bound => Some(format!("{answer} is not below {bound}, which was too high")),
+          Outcome::TooLow if value <= bound => Some(format!("{answer} is not above {bound}, which was too low")),
+          _ => None,
+        }
+      })
+      .map_or(Verdict::Submit, Verdict::OutOfBounds)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{parse_wait, History, Outcome, Verdict};
+
+  #[test]
+  fn test_parse_outcome() {
+    let page = |message: &str| format!("<html><body><main><article><p>{message}</p></article></main></body></html>");
+
+    assert_eq!(Outcome::parse(&page("That's the right answer! You are one gold star closer.")), Outcome::Correct);
+    assert_eq!(Outcome::parse(&page("That's not the right answer; your answer is too high.")), Outcome::TooHigh);
+    assert_eq!(Outcome::parse(&page("That's not the right answer; your answer is too low.")), Outcome::TooLow);
+    assert_eq!(Outcome::parse(&page("That's not the right answer. If you're stuck...")), Outcome::Wrong);
+    assert_eq!(
+      Outcome::parse(&page("You gave an answer too recently; you have to wait after submitting an answer before trying again. You have 1m 34s left to wait.")),
+      Outcome::RateLimited(Some(94))
+    );
+    assert_eq!(Outcome::parse(&page("You don't seem to be solving the right level. Did you already complete it?")), Outcome::AlreadySolved);
+    assert_eq!(Outcome::parse(""), Outcome::Unknown);
+
+    assert_eq!(parse_wait("You have 12s left to wait."), Some(12));
+    assert_eq!(parse_wait("You have a while left to wait."), None);
+  }
+
+  #[test]
+  fn test_check_history() {
+    let mut history = History::default();
+    history.record(1, 1, "100", Outcome::TooHigh);
+    history.record(1, 1, "20", Outcome::TooLow);
+    history.record(1, 1, "abc", Outcome::Wrong);
+    let now = history.0[0].time;
+
+    assert_eq!(history.check(1, 1, "50", now), Verdict::Submit);
+    assert_eq!(history.check(1, 1, "100", now), Verdict::KnownWrong(Outcome::TooHigh));
+    assert_eq!(history.check(1, 1, "abc", now), Verdict::KnownWrong(Outcome::Wrong));
+    assert!(matches!(history.check(1, 1, "150", now), Verdict::OutOfBounds(reason) if reason.contains("too high")));
+    assert!(matches!(history.check(1, 1, "20", now), Verdict::KnownWrong(Outcome::TooLow)));
+    assert!(matches!(history.check(1, 1, "3", now), Verdict::OutOfBounds(reason) if reason.contains("too low")));
+    assert_eq!(history.check(1, 2, "150", now), Verdict::Submit);
+
+    history.record(1, 1, "50", Outcome::RateLimited(Some(60)));
+    assert_eq!(history.check(1, 1, "50", now + 10), Verdict::Wait(50));
+    assert_eq!(history.check(1, 1, "50", now + 60), Verdict::Submit);
+
+    history.record(1, 1, "50", Outcome::Correct);
+    assert_eq!(history.check(1, 1, "60", now), Verdict::Solved(String::from("50")));
+  }
+}
@@ -1,49 +1,280 @@
 use std::error::Error;
 use std::env::current_dir;
-use std::fs::{File, read_to_string};
-use std::io::{BufRead, BufReader, Lines};
+use std::fs::read_to_string;
+use std::io::{self, Read};
 use std::fmt::{self, Display};
 use std::path::PathBuf;
+use std::str::FromStr;
 
-#[derive(Debug, PartialEq)]
-pub struct SolveError {
-  msg: String
+use crate::solution::SolveResult;
+
+pub mod grid;
+pub mod point;
+pub mod search;
+
+/// Why a day failed to produce its answers.
+///
+/// A panicking solver is caught and reported with its message, see
+/// [`catch_panic`](crate::solution::catch_panic). Parse errors point at the
+/// offending text. Parsers only know their own
+/// input, so the line is relative to the text they were given until
+/// [`SolveError::offset_lines`] moves it, and the file is filled in by the
+/// runner with [`SolveError::in_file`].
+#[derive(Debug)]
+pub enum SolveError {
+  Io { file: Option<String>, source: io::Error },
+  Parse { file: Option<String>, line: usize, column: usize, text: String, reason: String },
+  Logic(String),
+  Panic { message: String, location: Option<String>, backtrace: Option<String> },
 }
 
 impl SolveError {
-    pub fn new(msg: &str) -> Self { 
-      Self { msg: msg.to_string() }
+  pub fn logic(reason: impl Into<String>) -> Self {
+    Self::Logic(reason.into())
+  }
+
+  /// A parse error at `text`, a slice of `source`. The line and column are
+  /// those of `text` within `source`, or 1:1 when it is not part of it.
+  pub fn parse(source: &str, text: &str, reason: impl Into<String>) -> Self {
+    let (line, column) = position(source, text);
+
+    Self::Parse { file: None, line, column, text: text.to_string(), reason: reason.into() }
+  }
+
+  /// Makes the location of a parse error raised while parsing `text`, a slice
+  /// of `source`, relative to `source`.
+  pub fn within(mut self, source: &str, text: &str) -> Self {
+    if let Self::Parse { line, column, .. } = &mut self {
+      let (offset_line, offset_column) = position(source, text);
+
+      if *line == 1 {
+        *column += offset_column - 1;
+      }
+
+      *line += offset_line - 1;
     }
+
+    self
+  }
+
+  /// Moves a parse error down by `lines`, for parsers that were handed the
+  /// lines of the input one by one.
+  pub fn offset_lines(mut self, lines: usize) -> Self {
+    if let Self::Parse { line, .. } = &mut self {
+      *line += lines;
+    }
+
+    self
+  }
+
+  pub fn in_file(mut self, name: impl Display) -> Self {
+    if let Self::Io { file, .. } | Self::Parse { file, .. } = &mut self {
+      file.get_or_insert_with(|| name.to_string());
+    }
+
+    self
+  }
 }
 
 impl Display for SolveError {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    write!(f, "SolveError: {}", self.msg)
+    match self {
+      Self::Io { file: Some(file), source } => write!(f, "{file}: {source}"),
+      Self::Io { file: None, source } => write!(f, "{source}"),
+      Self::Parse { file, line, column, text, reason } => {
+        let file = file.as_deref().unwrap_or("<input>");
+        write!(f, "{file}:{line}:{column}: {reason}")?;
+
+        if !text.is_empty() {
+          write!(f, ", found {text:?}")?;
+        }
+
+        Ok(())
+      },
+      Self::Logic(reason) => write!(f, "{reason}"),
+      Self::Panic { message, location, backtrace } => {
+        match location {
+          Some(location) => write!(f, "Panicked at {location}: {message}")?,
+          None => write!(f, "Panicked: {message}")?,
+        }
+
+        match backtrace {
+          Some(backtrace) => write!(f, "\n{backtrace}"),
+          None => Ok(()),
+        }
+      },
+    }
   }
 }
 
-impl Error for SolveError {}
+impl Error for SolveError {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match self {
+      Self::Io { source, .. } => Some(source),
+      _ => None,
+    }
+  }
+}
 
-fn relative_path(file: &str) -> Result<PathBuf, Box<dyn Error>> {
-  let path = current_dir()?.join("input").join(file);
-  Ok(path)
+impl From<io::Error> for SolveError {
+  fn from(source: io::Error) -> Self {
+    Self::Io { file: None, source }
+  }
 }
 
-pub fn read_input_lines(file: &str) -> Result<Lines<BufReader<File>>, Box<dyn Error>> {
-  let path = relative_path(file)?;
-  let input = File::open(path)?;
-  Ok(BufReader::new(input).lines())
+/// The 1-based line and column at which `text` starts within `source`.
+fn position(source: &str, text: &str) -> (usize, usize) {
+  let offset = (text.as_ptr() as usize)
+    .checked_sub(source.as_ptr() as usize)
+    .filter(|offset| *offset <= source.len() && source.is_char_boundary(*offset))
+    .unwrap_or_default();
+
+  let before = &source[..offset];
+  let line = before.matches('\n').count() + 1;
+  let column = before.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+
+  (line, column)
 }
 
-pub fn read_input_lines_ok(file: &str) -> Result<Vec<String>, Box<dyn Error>> {
-  let path = relative_path(file)?;
-  let input = File::open(path)?;
-  Ok(BufReader::new(input).lines().filter(|l| l.is_ok()).flatten().collect())
+/// I/O errors compare by kind, as `io::Error` has no equality of its own.
+impl PartialEq for SolveError {
+  fn eq(&self, other: &Self) -> bool {
+    match (self, other) {
+      (Self::Io { file, source }, Self::Io { file: other_file, source: other_source }) => {
+        file == other_file && source.kind() == other_source.kind()
+      },
+      (
+        Self::Parse { file, line, column, text, reason },
+        Self::Parse { file: f, line: l, column: c, text: t, reason: r },
+      ) => (file, line, column, text, reason) == (f, l, c, t, r),
+      (Self::Logic(reason), Self::Logic(other)) => reason == other,
+      (Self::Panic { message, location, .. }, Self::Panic { message: other, location: other_location, .. }) => {
+        (message, location) == (other, other_location)
+      },
+      _ => false,
+    }
+  }
 }
 
-pub fn read_input_string(file: &str) -> Result<String, Box<dyn Error>> {
-  let path = relative_path(file)?;
-  let string = read_to_string(path)?;
-  Ok(string)
+/// Where the puzzle input of a day is read from.
+///
+/// A path given on the command line may point at a single input file or at a
+/// directory holding `dayN.txt` files; `-` reads the input from stdin.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Input {
+  Path(PathBuf),
+  Stdin,
 }
 
+impl Input {
+  pub fn from_arg(arg: &str) -> Self {
+    match arg {
+      "-" => Self::Stdin,
+      path => Self::Path(PathBuf::from(path)),
+    }
+  }
+
+  /// The `input` directory of the current working directory.
+  pub fn default_dir() -> Result<Self, Box<dyn Error>> {
+    Ok(Self::Path(current_dir()?.join("input")))
+  }
+
+  /// The puzzle samples in `input/examples`, next to their expected answers
+  /// in `answers.toml`.
+  pub fn examples_dir() -> Result<Self, Box<dyn Error>> {
+    Ok(Self::Path(current_dir()?.join("input").join("examples")))
+  }
+
+  /// The file the input of `day` is read from, `None` for stdin.
+  pub fn path(&self, day: usize) -> Option<PathBuf> {
+    match self {
+      Self::Stdin => None,
+      Self::Path(path) if path.is_dir() => Some(path.join(input_file(day))),
+      Self::Path(path) => Some(path.clone()),
+    }
+  }
+
+  /// The name of the input of `day` in error messages.
+  pub fn name(&self, day: usize) -> String {
+    match self.path(day) {
+      Some(path) => path.display().to_string(),
+      None => self.to_string(),
+    }
+  }
+
+  pub fn read(&self, day: usize) -> SolveResult<String> {
+    let result = match self.path(day) {
+      Some(path) => read_to_string(path),
+      None => {
+        let mut string = String::new();
+        io::stdin().read_to_string(&mut string).map(|_| string)
+      },
+    };
+
+    result.map_err(|error| SolveError::from(error).in_file(self.name(day)))
+  }
+}
+
+impl Display for Input {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Self::Path(path) => write!(f, "{}", path.display()),
+      Self::Stdin => write!(f, "<stdin>"),
+    }
+  }
+}
+
+pub fn input_file(day: usize) -> String {
+  format!("day{day}.txt")
+}
+
+pub fn input_lines(input: &str) -> Vec<String> {
+  input.lines().map(String::from).collect()
+}
+
+/// Parses every line of `input` with `f`, moving the errors to their line.
+pub fn parse_lines<'a, T>(input: &'a str, mut f: impl FnMut(&'a str) -> SolveResult<T>) -> SolveResult<Vec<T>> {
+  input
+    .lines()
+    .map(|line| f(line).map_err(|error| error.within(input, line)))
+    .collect()
+}
+
+/// Parses `text`, a slice of `line`, into a number.
+pub fn parse_number<T: FromStr>(line: &str, text: &str) -> SolveResult<T> {
+  text.parse().map_err(|_| SolveError::parse(line, text, "Expected a number"))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{parse_lines, parse_number, SolveError};
+
+  #[test]
+  fn test_parse_error_location() {
+    let input = "1 2\n3 x\n";
+    let error = SolveError::parse(input, &input[6..7], "Expected a number");
+    assert!(matches!(&error, SolveError::Parse { line: 2, column: 3, text, .. } if text == "x"));
+    assert_eq!(error.in_file("day1.txt").to_string(), "day1.txt:2:3: Expected a number, found \"x\"");
+
+    let error = SolveError::parse(input, "elsewhere", "Unexpected");
+    assert!(matches!(error, SolveError::Parse { line: 1, column: 1, .. }));
+
+    let line = &input[4..7];
+    let error = SolveError::parse(line, &line[2..], "Expected a number").within(input, line);
+    assert!(matches!(error, SolveError::Parse { line: 2, column: 3, .. }));
+    assert_eq!(error.offset_lines(3).to_string(), "<input>:5:3: Expected a number, found \"x\"");
+
+    let error = SolveError::parse(input, &input[input.len()..], "Expected more input");
+    assert_eq!(error.to_string(), "<input>:3:1: Expected more input");
+  }
+
+  #[test]
+  fn test_parse_lines() {
+    let parse = |line: &str| line.split(' ').map(|n| parse_number::<u32>(line, n)).collect::<Result<Vec<_>, _>>();
+
+    assert_eq!(parse_lines("1 2\n3 4", parse), Ok(vec![vec![1, 2], vec![3, 4]]));
+
+    let error = parse_lines("1 2\n3 4\n5 y", parse).unwrap_err();
+    assert_eq!(error.to_string(), "<input>:3:3: Expected a number, found \"y\"");
+  }
+}
@@ -0,0 +1,326 @@
+use std::fmt::{self, Display};
+use std::iter::successors;
+use std::ops::{Index, IndexMut};
+
+use crate::solution::SolveResult;
+use crate::utils::point::{Direction, Point2};
+use crate::utils::{parse_lines, SolveError};
+
+/// The steps to the eight orthogonal and diagonal neighbours, clockwise from
+/// up. Like every [`Point2`], they point up along the positive y axis.
+pub const ALL_DIRECTIONS: [Point2<isize>; 8] = [
+  Point2::new(0, 1),
+  Point2::new(1, 1),
+  Point2::new(1, 0),
+  Point2::new(1, -1),
+  Point2::new(0, -1),
+  Point2::new(-1, -1),
+  Point2::new(-1, 0),
+  Point2::new(-1, 1),
+];
+
+/// A rectangular grid of cells stored row by row.
+///
+/// Cells are addressed by `(x, y)` positions, `x` being the column and `y`
+/// the row counted from the top, or by their index in the row by row order.
+///
+/// Steps are [`Point2`] vectors or [`Direction`]s, which point up along the
+/// positive y axis like everywhere else: [`Direction::Up`] leads to the row
+/// above, whose `y` is one less. [`Grid::point`] and [`Grid::from_point`]
+/// convert between positions and points on that axis.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+  width: usize,
+  height: usize,
+  cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+  /// A grid of `width` by `height` cells, given row by row.
+  pub fn new(width: usize, height: usize, cells: Vec<T>) -> Self {
+    assert_eq!(cells.len(), width * height, "A {width}x{height} grid needs {} cells", width * height);
+    Self { width, height, cells }
+  }
+
+  pub fn filled(width: usize, height: usize, value: T) -> Self
+  where
+    T: Clone,
+  {
+    Self::new(width, height, vec![value; width * height])
+  }
+
+  /// Parses a grid with one character per cell and one row per line. Every
+  /// row must be as long as the first one. When `f` rejects a character, the
+  /// error points at it.
+  pub fn parse(input: &str, mut f: impl FnMut(char) -> Result<T, String>) -> SolveResult<Self> {
+    let width = input.lines().next().map_or(0, |line| line.chars().count());
+
+    let rows = parse_lines(input, |line| {
+      let row = line
+        .char_indices()
+        .map(|(i, c)| f(c).map_err(|reason| SolveError::parse(line, &line[i..i + c.len_utf8()], reason)))
+        .collect::<SolveResult<Vec<T>>>()?;
+
+      if row.len() != width {
+        return Err(SolveError::parse(line, line, format!("Expected a row of {width} cells")));
+      }
+
+      Ok(row)
+    })?;
+
+    if width == 0 {
+      return Err(SolveError::parse(input, &input[input.len()..], "Expected a grid of at least one cell"));
+    }
+
+    Ok(Self::new(width, rows.len(), rows.into_iter().flatten().collect()))
+  }
+
+  pub fn width(&self) -> usize {
+    self.width
+  }
+
+  pub fn height(&self) -> usize {
+    self.height
+  }
+
+  pub fn len(&self) -> usize {
+    self.cells.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.cells.is_empty()
+  }
+
+  /// The cells, row by row.
+  pub fn cells(&self) -> &[T] {
+    &self.cells
+  }
+
+  pub fn contains(&self, (x, y): (usize, usize)) -> bool {
+    x < self.width && y < self.height
+  }
+
+  /// The index of the cell at `position`, if it is inside the grid.
+  pub fn index_of(&self, position: (usize, usize)) -> Option<usize> {
+    self.contains(position).then(|| position.1 * self.width + position.0)
+  }
+
+  /// The position of the cell at `index`.
+  pub fn position(&self, index: usize) -> (usize, usize) {
+    (index % self.width, index / self.width)
+  }
+
+  pub fn get(&self, position: (usize, usize)) -> Option<&T> {
+    self.index_of(position).map(|index| &self.cells[index])
+  }
+
+  pub fn get_mut(&mut self, position: (usize, usize)) -> Option<&mut T> {
+    self.index_of(position).map(|index| &mut self.cells[index])
+  }
+
+  /// The point of the cell at `position`, whose y is the row negated so
+  /// that it grows upwards.
+  pub fn point(&self, (x, y): (usize, usize)) -> Point2<isize> {
+    Point2::new(x as isize, -(y as isize))
+  }
+
+  /// The position of the cell at `point`, if it is inside the grid. The
+  /// inverse of [`Grid::point`].
+  pub fn from_point(&self, point: Point2<isize>) -> Option<(usize, usize)> {
+    let position = (usize::try_from(point.x).ok()?, usize::try_from(-point.y).ok()?);
+    self.contains(position).then_some(position)
+  }
+
+  /// The position one `step` away from `position`, if it is inside the grid.
+  pub fn step(&self, position: (usize, usize), step: impl Into<Point2<isize>>) -> Option<(usize, usize)> {
+    self.from_point(self.point(position) + step.into())
+  }
+
+  /// The positions of the orthogonal neighbours of `position` inside the
+  /// grid, clockwise from up.
+  pub fn neighbours4(&self, position: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+    Direction::ALL.into_iter().filter_map(move |direction| self.step(position, direction))
+  }
+
+  /// Like [`Grid::neighbours4`], including the diagonal neighbours.
+  pub fn neighbours8(&self, position: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+    ALL_DIRECTIONS.into_iter().filter_map(move |direction| self.step(position, direction))
+  }
+
+  /// The positions of every cell, row by row.
+  pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+    (0..self.len()).map(|index| self.position(index))
+  }
+
+  /// Every cell with its position, row by row.
+  pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+    self.cells.iter().enumerate().map(|(index, cell)| (self.position(index), cell))
+  }
+
+  /// The position of the first cell, row by row, that matches `predicate`.
+  pub fn find(&self, predicate: impl FnMut(&T) -> bool) -> Option<(usize, usize)> {
+    self.cells.iter().position(predicate).map(|index| self.position(index))
+  }
+
+  pub fn row(&self, y: usize) -> &[T] {
+    &self.cells[y * self.width..(y + 1) * self.width]
+  }
+
+  pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+    self.cells.chunks(self.width.max(1))
+  }
+
+  /// The cells of column `x`, from top to bottom.
+  pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+    assert!(x < self.width, "Column {x} is outside a grid of width {}", self.width);
+    self.cells.iter().skip(x).step_by(self.width)
+  }
+
+  /// The cells met walking from `start` in `direction` steps until leaving
+  /// the grid, without `start` itself.
+  pub fn ray(&self, start: (usize, usize), direction: impl Into<Point2<isize>>) -> impl Iterator<Item = ((usize, usize), &T)> {
+    let direction = direction.into();
+
+    successors(self.step(start, direction), move |&position| self.step(position, direction))
+      .map(|position| (position, &self[position]))
+  }
+
+  pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+    Grid::new(self.width, self.height, self.cells.iter().map(f).collect())
+  }
+
+  /// The grid mirrored along its main diagonal, so that rows become columns.
+  pub fn transpose(&self) -> Self
+  where
+    T: Clone,
+  {
+    let cells = (0..self.width).flat_map(|x| self.column(x).cloned()).collect();
+    Self::new(self.height, self.width, cells)
+  }
+
+  /// The grid turned a quarter clockwise.
+  pub fn rotate_clockwise(&self) -> Self
+  where
+    T: Clone,
+  {
+    let cells = (0..self.width).flat_map(|x| (0..self.height).rev().map(move |y| self[(x, y)].clone())).collect();
+    Self::new(self.height, self.width, cells)
+  }
+
+  /// The grid turned a quarter counterclockwise.
+  pub fn rotate_counterclockwise(&self) -> Self
+  where
+    T: Clone,
+  {
+    let cells = (0..self.width).rev().flat_map(|x| (0..self.height).map(move |y| self[(x, y)].clone())).collect();
+    Self::new(self.height, self.width, cells)
+  }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+  type Output = T;
+
+  fn index(&self, position: (usize, usize)) -> &T {
+    match self.index_of(position) {
+      Some(index) => &self.cells[index],
+      None => panic!("{position:?} is outside a {}x{} grid", self.width, self.height),
+    }
+  }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+  fn index_mut(&mut self, position: (usize, usize)) -> &mut T {
+    match self.index_of(position) {
+      Some(index) => &mut self.cells[index],
+      None => panic!("{position:?} is outside a {}x{} grid", self.width, self.height),
+    }
+  }
+}
+
+/// Prints the rows on separate lines, with the cells of a row side by side.
+impl<T: Display> Display for Grid<T> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for (y, row) in self.rows().enumerate() {
+      if y > 0 {
+        writeln!(f)?;
+      }
+
+      row.iter().try_for_each(|cell| write!(f, "{cell}"))?;
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Grid;
+  use crate::utils::point::{Direction, Point2};
+
+  fn letters(input: &str) -> Grid<char> {
+    Grid::parse(input, |c| if c.is_ascii_lowercase() { Ok(c) } else { Err(String::from("Expected a letter")) }).unwrap()
+  }
+
+  #[test]
+  fn test_parse() {
+    let grid = letters("abc\ndef");
+    assert_eq!((grid.width(), grid.height(), grid.len()), (3, 2, 6));
+    assert_eq!(grid.cells(), ['a', 'b', 'c', 'd', 'e', 'f']);
+
+    let digits = |c: char| c.to_digit(10).ok_or_else(|| String::from("Expected a digit"));
+    assert_eq!(Grid::parse("12\n3x", digits).unwrap_err().to_string(), "<input>:2:2: Expected a digit, found \"x\"");
+    assert_eq!(Grid::parse("12\n345", digits).unwrap_err().to_string(), "<input>:2:1: Expected a row of 2 cells, found \"345\"");
+    assert!(Grid::parse("", digits).is_err());
+  }
+
+  #[test]
+  fn test_positions() {
+    let mut grid = letters("abc\ndef");
+    assert_eq!(grid.index_of((2, 1)), Some(5));
+    assert_eq!(grid.index_of((3, 0)), None);
+    assert_eq!(grid.position(4), (1, 1));
+    assert_eq!((grid[(1, 1)], grid.get((0, 2))), ('e', None));
+    assert_eq!(grid.find(|&c| c == 'f'), Some((2, 1)));
+
+    grid[(1, 0)] = 'x';
+    assert_eq!(grid.row(0), ['a', 'x', 'c']);
+  }
+
+  #[test]
+  fn test_neighbours() {
+    let grid = letters("abc\ndef\nghi");
+    assert_eq!(grid.neighbours4((0, 0)).collect::<Vec<_>>(), [(1, 0), (0, 1)]);
+    assert_eq!(grid.neighbours4((1, 1)).collect::<Vec<_>>(), [(1, 0), (2, 1), (1, 2), (0, 1)]);
+    assert_eq!(grid.neighbours8((2, 2)).collect::<Vec<_>>(), [(2, 1), (1, 2), (1, 1)]);
+    assert_eq!(grid.neighbours8((1, 1)).count(), 8);
+
+    assert_eq!(grid.step((1, 1), Direction::Up), Some((1, 0)));
+    assert_eq!(grid.step((1, 1), Point2::new(1, -1)), Some((2, 2)));
+    assert_eq!(grid.step((0, 0), Direction::Left), None);
+    assert_eq!(grid.point((2, 1)), Point2::new(2, -1));
+    assert_eq!(grid.from_point(grid.point((2, 1)) + Direction::Up.unit()), Some((2, 0)));
+    assert_eq!(grid.from_point(Point2::new(1, 1)), None);
+  }
+
+  #[test]
+  fn test_lines() {
+    let grid = letters("abc\ndef\nghi");
+    assert_eq!(grid.rows().collect::<Vec<_>>(), [['a', 'b', 'c'], ['d', 'e', 'f'], ['g', 'h', 'i']]);
+    assert_eq!(grid.column(1).collect::<String>(), "beh");
+    assert_eq!(grid.ray((0, 0), Point2::new(1, -1)).map(|(_, c)| c).collect::<String>(), "ei");
+    assert_eq!(grid.ray((1, 1), Direction::Left).collect::<Vec<_>>(), [((0, 1), &'d')]);
+    assert_eq!(grid.ray((0, 1), Direction::Left).count(), 0);
+    assert_eq!(grid.ray((1, 2), Direction::Up).map(|(_, c)| c).collect::<String>(), "eb");
+  }
+
+  #[test]
+  fn test_transform() {
+    let grid = letters("abc\ndef");
+    assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf");
+    assert_eq!(grid.rotate_clockwise().to_string(), "da\neb\nfc");
+    assert_eq!(grid.rotate_counterclockwise().to_string(), "cf\nbe\nad");
+    assert_eq!(grid.rotate_clockwise().rotate_counterclockwise(), grid);
+    assert_eq!(grid.map(|c| c.to_ascii_uppercase()).to_string(), "ABC\nDEF");
+    assert_eq!(Grid::filled(2, 1, 0).to_string(), "00");
+  }
+}
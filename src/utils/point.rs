@@ -0,0 +1,367 @@
+use std::fmt::{self, Debug, Display};
+use std::hash::Hash;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// The signed integer types points are made of.
+pub trait Coordinate:
+  Copy + Ord + Default + Hash + Debug + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Neg<Output = Self>
+{
+  const ZERO: Self;
+  const ONE: Self;
+
+  fn abs(self) -> Self;
+  fn signum(self) -> Self;
+}
+
+macro_rules! coordinates {
+  ($($type:ty),*) => {
+    $(impl Coordinate for $type {
+      const ZERO: Self = 0;
+      const ONE: Self = 1;
+
+      fn abs(self) -> Self {
+        <$type>::abs(self)
+      }
+
+      fn signum(self) -> Self {
+        <$type>::signum(self)
+      }
+    })*
+  };
+}
+
+coordinates!(i8, i16, i32, i64, i128, isize);
+
+/// A point, or a vector, in the plane. The y axis points up, so that turning
+/// left is counterclockwise.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point2<T> {
+  pub x: T,
+  pub y: T,
+}
+
+impl<T> Point2<T> {
+  pub const fn new(x: T, y: T) -> Self {
+    Self { x, y }
+  }
+}
+
+impl<T: Coordinate> Point2<T> {
+  pub const ORIGIN: Self = Self::new(T::ZERO, T::ZERO);
+
+  pub fn manhattan(self, other: Self) -> T {
+    let delta = (other - self).abs();
+    delta.x + delta.y
+  }
+
+  /// The number of king moves between the points.
+  pub fn chebyshev(self, other: Self) -> T {
+    let delta = (other - self).abs();
+    delta.x.max(delta.y)
+  }
+
+  pub fn abs(self) -> Self {
+    Self::new(self.x.abs(), self.y.abs())
+  }
+
+  /// The vector with the sign of each coordinate, one step towards the
+  /// direction of `self` along both axes.
+  pub fn signum(self) -> Self {
+    Self::new(self.x.signum(), self.y.signum())
+  }
+
+  /// The vector turned a quarter counterclockwise around the origin.
+  pub fn rotate_left(self) -> Self {
+    Self::new(-self.y, self.x)
+  }
+
+  /// The vector turned a quarter clockwise around the origin.
+  pub fn rotate_right(self) -> Self {
+    Self::new(self.y, -self.x)
+  }
+}
+
+impl<T> From<(T, T)> for Point2<T> {
+  fn from((x, y): (T, T)) -> Self {
+    Self::new(x, y)
+  }
+}
+
+impl<T> From<Point2<T>> for (T, T) {
+  fn from(point: Point2<T>) -> Self {
+    (point.x, point.y)
+  }
+}
+
+impl<T: Display> Display for Point2<T> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "({}, {})", self.x, self.y)
+  }
+}
+
+/// A point, or a vector, in space.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point3<T> {
+  pub x: T,
+  pub y: T,
+  pub z: T,
+}
+
+impl<T> Point3<T> {
+  pub const fn new(x: T, y: T, z: T) -> Self {
+    Self { x, y, z }
+  }
+}
+
+impl<T: Coordinate> Point3<T> {
+  pub const ORIGIN: Self = Self::new(T::ZERO, T::ZERO, T::ZERO);
+
+  pub fn manhattan(self, other: Self) -> T {
+    let delta = (other - self).abs();
+    delta.x + delta.y + delta.z
+  }
+
+  pub fn chebyshev(self, other: Self) -> T {
+    let delta = (other - self).abs();
+    delta.x.max(delta.y).max(delta.z)
+  }
+
+  pub fn abs(self) -> Self {
+    Self::new(self.x.abs(), self.y.abs(), self.z.abs())
+  }
+
+  pub fn signum(self) -> Self {
+    Self::new(self.x.signum(), self.y.signum(), self.z.signum())
+  }
+}
+
+impl<T> From<(T, T, T)> for Point3<T> {
+  fn from((x, y, z): (T, T, T)) -> Self {
+    Self::new(x, y, z)
+  }
+}
+
+impl<T: Display> Display for Point3<T> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "({}, {}, {})", self.x, self.y, self.z)
+  }
+}
+
+/// Implements the component-wise operators, and scaling by a coordinate.
+macro_rules! operators {
+  ($point:ident { $($field:ident),* }) => {
+    impl<T: Add<Output = T>> Add for $point<T> {
+      type Output = Self;
+
+      fn add(self, other: Self) -> Self {
+        Self { $($field: self.$field + other.$field),* }
+      }
+    }
+
+    impl<T: Sub<Output = T>> Sub for $point<T> {
+      type Output = Self;
+
+      fn sub(self, other: Self) -> Self {
+        Self { $($field: self.$field - other.$field),* }
+      }
+    }
+
+    impl<T: Mul<Output = T> + Copy> Mul<T> for $point<T> {
+      type Output = Self;
+
+      fn mul(self, factor: T) -> Self {
+        Self { $($field: self.$field * factor),* }
+      }
+    }
+
+    impl<T: Neg<Output = T>> Neg for $point<T> {
+      type Output = Self;
+
+      fn neg(self) -> Self {
+        Self { $($field: -self.$field),* }
+      }
+    }
+
+    impl<T: Add<Output = T> + Copy> AddAssign for $point<T> {
+      fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+      }
+    }
+
+    impl<T: Sub<Output = T> + Copy> SubAssign for $point<T> {
+      fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+      }
+    }
+  };
+}
+
+operators!(Point2 { x, y });
+operators!(Point3 { x, y, z });
+
+/// The four directions of the plane, with up along the positive y axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+  Up,
+  Right,
+  Down,
+  Left,
+}
+
+impl Direction {
+  /// Every direction, clockwise from up.
+  pub const ALL: [Self; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
+  /// The vector of one step in the direction.
+  pub fn unit<T: Coordinate>(self) -> Point2<T> {
+    match self {
+      Self::Up => Point2::new(T::ZERO, T::ONE),
+      Self::Right => Point2::new(T::ONE, T::ZERO),
+      Self::Down => Point2::new(T::ZERO, -T::ONE),
+      Self::Left => Point2::new(-T::ONE, T::ZERO),
+    }
+  }
+
+  pub fn turn_left(self) -> Self {
+    Self::ALL[(self as usize + 3) % 4]
+  }
+
+  pub fn turn_right(self) -> Self {
+    Self::ALL[(self as usize + 1) % 4]
+  }
+
+  pub fn reverse(self) -> Self {
+    Self::ALL[(self as usize + 2) % 4]
+  }
+
+  /// The direction written as its initial, `U`, `R`, `D` or `L`.
+  pub fn from_initial(initial: char) -> Option<Self> {
+    match initial {
+      'U' => Some(Self::Up),
+      'R' => Some(Self::Right),
+      'D' => Some(Self::Down),
+      'L' => Some(Self::Left),
+      _ => None,
+    }
+  }
+}
+
+impl<T: Coordinate> From<Direction> for Point2<T> {
+  fn from(direction: Direction) -> Self {
+    direction.unit()
+  }
+}
+
+/// The smallest axis-aligned rectangle holding a set of points, bounds
+/// included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bounds<T> {
+  pub min: Point2<T>,
+  pub max: Point2<T>,
+}
+
+impl<T: Coordinate> Bounds<T> {
+  /// The bounds of a single point.
+  pub fn new(point: Point2<T>) -> Self {
+    Self { min: point, max: point }
+  }
+
+  /// The bounds of `points`, `None` when there are none.
+  pub fn from_points(points: impl IntoIterator<Item = Point2<T>>) -> Option<Self> {
+    let mut points = points.into_iter();
+    let first = points.next()?;
+
+    Some(points.fold(Self::new(first), Self::extend))
+  }
+
+  /// The bounds grown to hold `point`.
+  pub fn extend(self, point: Point2<T>) -> Self {
+    Self {
+      min: Point2::new(self.min.x.min(point.x), self.min.y.min(point.y)),
+      max: Point2::new(self.max.x.max(point.x), self.max.y.max(point.y)),
+    }
+  }
+
+  pub fn contains(&self, point: Point2<T>) -> bool {
+    (self.min.x..=self.max.x).contains(&point.x) && (self.min.y..=self.max.y).contains(&point.y)
+  }
+
+  /// The number of columns covered.
+  pub fn width(&self) -> T {
+    self.max.x - self.min.x + T::ONE
+  }
+
+  /// The number of rows covered.
+  pub fn height(&self) -> T {
+    self.max.y - self.min.y + T::ONE
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Bounds, Direction, Point2, Point3};
+
+  #[test]
+  fn test_arithmetic() {
+    let (a, b) = (Point2::new(1, 2), Point2::new(4, -2));
+    assert_eq!(a + b, Point2::new(5, 0));
+    assert_eq!(a - b, Point2::new(-3, 4));
+    assert_eq!(a * 3, Point2::new(3, 6));
+    assert_eq!(-a, Point2::new(-1, -2));
+
+    let mut c = a;
+    c += b;
+    c -= Point2::new(1, 1);
+    assert_eq!(c, Point2::new(4, -1));
+
+    assert_eq!(Point3::new(1, 2, 3) + Point3::new(1, 1, 1) * 2, Point3::new(3, 4, 5));
+    assert_eq!(Point2::from((7, 8)), Point2::new(7, 8));
+    assert_eq!(<(i32, i32)>::from(a), (1, 2));
+    assert_eq!(Point3::new(1, -2, 3).to_string(), "(1, -2, 3)");
+  }
+
+  #[test]
+  fn test_distances() {
+    let (a, b) = (Point2::new(1, 2), Point2::new(4, -2));
+    assert_eq!((a.manhattan(b), a.chebyshev(b)), (7, 4));
+    assert_eq!((b - a).signum(), Point2::new(1, -1));
+    assert_eq!(Point2::<i64>::ORIGIN.manhattan(Point2::new(-3, 3)), 6);
+
+    let (a, b) = (Point3::new(0, 0, 0), Point3::new(-1, 5, 2));
+    assert_eq!((a.manhattan(b), a.chebyshev(b), b.signum()), (8, 5, Point3::new(-1, 1, 1)));
+  }
+
+  #[test]
+  fn test_rotation() {
+    let point = Point2::new(2, 1);
+    assert_eq!(point.rotate_left(), Point2::new(-1, 2));
+    assert_eq!(point.rotate_right(), Point2::new(1, -2));
+    assert_eq!(point.rotate_left().rotate_left().rotate_left().rotate_left(), point);
+  }
+
+  #[test]
+  fn test_direction() {
+    assert_eq!(Direction::Up.unit::<i32>(), Point2::new(0, 1));
+    assert_eq!(Direction::Left.unit::<i8>() * 3, Point2::new(-3, 0));
+
+    for direction in Direction::ALL {
+      assert_eq!(direction.turn_left().unit::<i32>(), direction.unit::<i32>().rotate_left());
+      assert_eq!(direction.turn_right().unit::<i32>(), direction.unit::<i32>().rotate_right());
+      assert_eq!(direction.reverse().unit::<i32>(), -direction.unit::<i32>());
+    }
+
+    assert_eq!(Direction::from_initial('D'), Some(Direction::Down));
+    assert_eq!(Direction::from_initial('X'), None);
+    assert_eq!(Point2::<i64>::from(Direction::Down), Point2::new(0, -1));
+  }
+
+  #[test]
+  fn test_bounds() {
+    let bounds = Bounds::from_points([Point2::new(1, 5), Point2::new(-2, 3), Point2::new(4, 4)]).unwrap();
+    assert_eq!((bounds.min, bounds.max), (Point2::new(-2, 3), Point2::new(4, 5)));
+    assert_eq!((bounds.width(), bounds.height()), (7, 3));
+    assert!(bounds.contains(Point2::new(0, 4)));
+    assert!(!bounds.contains(Point2::new(0, 6)));
+    assert_eq!(Bounds::<i32>::from_points([]), None);
+  }
+}
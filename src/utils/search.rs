@@ -0,0 +1,276 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// What a search found: the cost of reaching every node it settled from the
+/// nearest start, and the way back along a cheapest path.
+#[derive(Debug, Clone)]
+pub struct Search<N, C> {
+  costs: HashMap<N, C>,
+  previous: HashMap<N, N>,
+  goal: Option<N>,
+}
+
+impl<N: Eq + Hash + Clone, C: Copy> Search<N, C> {
+  fn new() -> Self {
+    Self { costs: HashMap::new(), previous: HashMap::new(), goal: None }
+  }
+
+  /// The goal the search stopped at, if any was reached.
+  pub fn goal(&self) -> Option<&N> {
+    self.goal.as_ref()
+  }
+
+  /// The cost of the goal the search stopped at.
+  pub fn goal_cost(&self) -> Option<C> {
+    self.goal.as_ref().and_then(|goal| self.cost(goal))
+  }
+
+  /// The cost of the cheapest path to `node`, if it was reached.
+  pub fn cost(&self, node: &N) -> Option<C> {
+    self.costs.get(node).copied()
+  }
+
+  /// Every node reached, with its cost, in no particular order.
+  pub fn costs(&self) -> impl Iterator<Item = (&N, C)> {
+    self.costs.iter().map(|(node, cost)| (node, *cost))
+  }
+
+  /// The nodes of a cheapest path from a start to `node`, both included.
+  pub fn path(&self, node: &N) -> Option<Vec<N>> {
+    self.costs.contains_key(node).then(|| {
+      let mut path = vec![node.clone()];
+
+      while let Some(previous) = self.previous.get(path.last().unwrap()) {
+        path.push(previous.clone());
+      }
+
+      path.reverse();
+      path
+    })
+  }
+
+  /// The path to the goal the search stopped at.
+  pub fn goal_path(&self) -> Option<Vec<N>> {
+    self.goal.as_ref().and_then(|goal| self.path(goal))
+  }
+}
+
+/// Breadth-first search over unweighted edges from every node in `starts` at
+/// once, so that each node is reached from its nearest start. Stops at the
+/// first node matching `is_goal`; pass `|_| false` to reach everything.
+pub fn bfs<N, I>(
+  starts: impl IntoIterator<Item = N>,
+  mut neighbours: impl FnMut(&N) -> I,
+  mut is_goal: impl FnMut(&N) -> bool,
+) -> Search<N, usize>
+where
+  N: Eq + Hash + Clone,
+  I: IntoIterator<Item = N>,
+{
+  let mut search = Search::new();
+  let mut queue = VecDeque::new();
+
+  for start in starts {
+    if let Entry::Vacant(entry) = search.costs.entry(start.clone()) {
+      entry.insert(0);
+      queue.push_back(start);
+    }
+  }
+
+  while let Some(node) = queue.pop_front() {
+    if is_goal(&node) {
+      search.goal = Some(node);
+      break;
+    }
+
+    let cost = search.costs[&node] + 1;
+
+    for next in neighbours(&node) {
+      if let Entry::Vacant(entry) = search.costs.entry(next.clone()) {
+        entry.insert(cost);
+        search.previous.insert(next.clone(), node.clone());
+        queue.push_back(next);
+      }
+    }
+  }
+
+  search
+}
+
+/// Dijkstra's algorithm over edges weighted by the costs `neighbours`
+/// returns next to each node, which must not be negative. Starts and goals
+/// work as in [`bfs`].
+pub fn dijkstra<N, C, I>(
+  starts: impl IntoIterator<Item = N>,
+  neighbours: impl FnMut(&N) -> I,
+  is_goal: impl FnMut(&N) -> bool,
+) -> Search<N, C>
+where
+  N: Eq + Hash + Clone,
+  C: Ord + Copy + Default + Add<Output = C>,
+  I: IntoIterator<Item = (N, C)>,
+{
+  astar(starts, neighbours, |_| C::default(), is_goal)
+}
+
+/// A* search: Dijkstra's algorithm expanding first the nodes whose cost plus
+/// `heuristic` is the lowest. The heuristic must never overestimate the
+/// remaining cost to a goal for the paths found to be the cheapest.
+pub fn astar<N, C, I>(
+  starts: impl IntoIterator<Item = N>,
+  mut neighbours: impl FnMut(&N) -> I,
+  mut heuristic: impl FnMut(&N) -> C,
+  mut is_goal: impl FnMut(&N) -> bool,
+) -> Search<N, C>
+where
+  N: Eq + Hash + Clone,
+  C: Ord + Copy + Default + Add<Output = C>,
+  I: IntoIterator<Item = (N, C)>,
+{
+  let mut search = Search::new();
+  let mut heap = BinaryHeap::new();
+
+  for start in starts {
+    if let Entry::Vacant(entry) = search.costs.entry(start.clone()) {
+      entry.insert(C::default());
+      heap.push(State { priority: heuristic(&start), cost: C::default(), node: start });
+    }
+  }
+
+  while let Some(State { cost, node, .. }) = heap.pop() {
+    // A cheaper path to the node was settled after this entry was queued.
+    if search.costs.get(&node).is_some_and(|&best| cost > best) {
+      continue;
+    }
+
+    if is_goal(&node) {
+      search.goal = Some(node);
+      break;
+    }
+
+    for (next, weight) in neighbours(&node) {
+      let cost = cost + weight;
+
+      if search.costs.get(&next).is_none_or(|&best| cost < best) {
+        search.costs.insert(next.clone(), cost);
+        search.previous.insert(next.clone(), node.clone());
+        heap.push(State { priority: cost + heuristic(&next), cost, node: next });
+      }
+    }
+  }
+
+  search
+}
+
+/// An entry of the A* queue, ordered so that the lowest priority is popped
+/// first from the max-heap.
+struct State<N, C> {
+  priority: C,
+  cost: C,
+  node: N,
+}
+
+impl<N, C: Ord> PartialEq for State<N, C> {
+  fn eq(&self, other: &Self) -> bool {
+    self.cmp(other) == Ordering::Equal
+  }
+}
+
+impl<N, C: Ord> Eq for State<N, C> {}
+
+impl<N, C: Ord> PartialOrd for State<N, C> {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl<N, C: Ord> Ord for State<N, C> {
+  fn cmp(&self, other: &Self) -> Ordering {
+    // Among equal priorities, prefer the deeper node, which is closer to a
+    // goal when the heuristic is informative.
+    other.priority.cmp(&self.priority).then_with(|| self.cost.cmp(&other.cost))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{astar, bfs, dijkstra};
+  use crate::utils::grid::Grid;
+
+  fn maze() -> Grid<char> {
+    Grid::parse("S..#....\n.#.#.##.\n.#...#..\n.####.#.\n......#E", Ok).unwrap()
+  }
+
+  fn open(grid: &Grid<char>, position: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+    grid.neighbours4(position).filter(|&next| grid[next] != '#')
+  }
+
+  #[test]
+  fn test_bfs() {
+    let grid = maze();
+    let end = grid.find(|&c| c == 'E').unwrap();
+
+    let search = bfs([(0, 0)], |&position| open(&grid, position), |&position| position == end);
+    assert_eq!((search.goal(), search.goal_cost()), (Some(&end), Some(15)));
+
+    let path = search.goal_path().unwrap();
+    assert_eq!((path.first(), path.last(), path.len()), (Some(&(0, 0)), Some(&end), 16));
+    assert!(path.windows(2).all(|step| grid.neighbours4(step[0]).any(|next| next == step[1])));
+
+    // Without a goal every reachable square is visited.
+    let search = bfs([(0, 0)], |&position| open(&grid, position), |_| false);
+    assert_eq!(search.goal(), None);
+    assert_eq!(search.costs().count(), grid.cells().iter().filter(|&&c| c != '#').count());
+  }
+
+  #[test]
+  fn test_multi_source() {
+    let grid = maze();
+
+    // Each square is reached from the nearer of the two corners.
+    let search = bfs([(0, 0), (7, 4)], |&position| open(&grid, position), |_| false);
+    assert_eq!(search.cost(&(7, 0)), Some(4));
+    assert_eq!(search.cost(&(2, 0)), Some(2));
+    assert_eq!(search.path(&(7, 0)).unwrap().first(), Some(&(7, 4)));
+    assert_eq!(search.path(&(3, 0)), None);
+  }
+
+  #[test]
+  fn test_weighted() {
+    // Going down costs the digit of the square entered, going right is free.
+    let grid = Grid::parse("111\n952\n991", |c| c.to_digit(10).ok_or_else(String::new)).unwrap();
+    let neighbours = |&(x, y): &(usize, usize)| {
+      [(x + 1, y), (x, y + 1)]
+        .into_iter()
+        .filter_map(|next| grid.get(next).map(|&digit| (next, if next.1 > y { digit } else { 0 })))
+        .collect::<Vec<_>>()
+    };
+
+    let search = dijkstra([(0, 0)], neighbours, |&position| position == (2, 2));
+    assert_eq!(search.goal_cost(), Some(3));
+    assert_eq!(search.goal_path().unwrap(), [(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)]);
+
+    // Every row left to go down costs at least one.
+    let search = astar([(0, 0)], neighbours, |&(_, y)| (2 - y) as u32, |&position| position == (2, 2));
+    assert_eq!(search.goal_cost(), Some(3));
+  }
+
+  #[test]
+  fn test_astar() {
+    let grid = maze();
+    let end = grid.find(|&c| c == 'E').unwrap();
+    let manhattan = |&(x, y): &(usize, usize)| x.abs_diff(end.0) + y.abs_diff(end.1);
+
+    let search = astar([(0, 0)], |&position| open(&grid, position).map(|next| (next, 1)), manhattan, |&position| position == end);
+    assert_eq!(search.goal_cost(), Some(15));
+
+    // The heuristic steers the search away from part of the maze.
+    let explored = search.costs().count();
+    let search = dijkstra([(0, 0)], |&position| open(&grid, position).map(|next| (next, 1)), |&position| position == end);
+    assert_eq!(search.goal_cost(), Some(15));
+    assert!(explored <= search.costs().count());
+  }
+}
@@ -0,0 +1,141 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use serde::Deserialize;
+
+/// Notices when files are modified by polling their modification time and
+/// size.
+#[derive(Debug)]
+pub struct Watcher {
+  paths: Vec<PathBuf>,
+  stamps: Vec<Option<(SystemTime, u64)>>,
+}
+
+impl Watcher {
+  pub fn new(paths: Vec<PathBuf>) -> Self {
+    let stamps = paths.iter().map(stamp).collect();
+    Self { paths, stamps }
+  }
+
+  /// The files modified, created or removed since the previous call.
+  pub fn changed(&mut self) -> Vec<PathBuf> {
+    self
+      .paths
+      .iter()
+      .zip(&mut self.stamps)
+      .filter_map(|(path, previous)| {
+        let stamp = stamp(path);
+        (stamp != std::mem::replace(previous, stamp)).then(|| path.clone())
+      })
+      .collect()
+  }
+}
+
+fn stamp(path: &PathBuf) -> Option<(SystemTime, u64)> {
+  let metadata = fs::metadata(path).ok()?;
+  Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RunError {
+  pub kind: String,
+  pub message: String,
+}
+
+/// The answers and timings of a day, read back from the records printed with
+/// `--format json`. Timings are in microseconds.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Run {
+  pub part1: Option<String>,
+  pub part2: Option<String>,
+  pub parse_us: Option<u64>,
+  pub part1_us: Option<u64>,
+  pub part2_us: Option<u64>,
+  pub error: Option<RunError>,
+}
+
+#[derive(Deserialize)]
+struct Summary {
+  days: Vec<Run>,
+}
+
+/// The runs of the days in the JSON output of the runner.
+pub fn parse_runs(json: &str) -> serde_json::Result<Vec<Run>> {
+  Ok(serde_json::from_str::<Summary>(json)?.days)
+}
+
+/// Describes `current` line by line, next to how its answers and timings
+/// changed since `previous`.
+pub fn diff(previous: Option<&Run>, current: &Run) -> Vec<String> {
+  if let Some(error) = &current.error {
+    return vec![format!("Error ({}): {}", error.kind, error.message)];
+  }
+
+  let previous = previous.filter(|previous| previous.error.is_none());
+  let steps = [
+    ("Parse", None, current.parse_us, previous.and_then(|previous| previous.parse_us)),
+    ("Part 1", Some((&current.part1, previous.map(|previous| &previous.part1))), current.part1_us, previous.and_then(|previous| previous.part1_us)),
+    ("Part 2", Some((&current.part2, previous.map(|previous| &previous.part2))), current.part2_us, previous.and_then(|previous| previous.part2_us)),
+  ];
+
+  steps
+    .into_iter()
+    .filter_map(|(step, answers, time, previous_time)| {
+      let time = time?;
+
+      let answer = match answers {
+        Some((Some(answer), Some(Some(previous)))) if previous != answer => format!("{answer:<16} (was {previous})"),
+        Some((Some(answer), Some(Some(_)))) => format!("{answer:<16} (unchanged)"),
+        Some((Some(answer), _)) => format!("{answer:<16}"),
+        _ => String::new(),
+      };
+
+      let change = match previous_time {
+        Some(previous) if previous > 0 => format!(" ({:+.1}%)", (time as f64 - previous as f64) / previous as f64 * 100.0),
+        _ => String::new(),
+      };
+
+      Some(format!("{step:<6} | {answer:<40} | {time:>8} µs{change}"))
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{fs, process};
+
+  use super::{diff, parse_runs, Run, Watcher};
+
+  #[test]
+  fn test_watcher() {
+    let path = std::env::temp_dir().join(format!("aoc2022-watch-{}.txt", process::id()));
+    fs::write(&path, "1").unwrap();
+
+    let mut watcher = Watcher::new(vec![path.clone()]);
+    assert!(watcher.changed().is_empty());
+
+    fs::write(&path, "12").unwrap();
+    assert_eq!(watcher.changed(), std::slice::from_ref(&path));
+    assert!(watcher.changed().is_empty());
+
+    fs::remove_file(&path).unwrap();
+    assert_eq!(watcher.changed(), [path]);
+  }
+
+  #[test]
+  fn test_diff() {
+    let json = r#"{"days": [{"day": 1, "title": "Calorie Counting", "part1": "24000", "part2": null, "parse_us": 10, "part1_us": 20, "part2_us": null, "total_us": 30, "error": null}], "total_us": 30, "wall_us": 35}"#;
+    let runs = parse_runs(json).unwrap();
+    assert_eq!(runs, [Run { part1: Some(String::from("24000")), parse_us: Some(10), part1_us: Some(20), ..Run::default() }]);
+
+    let first = diff(None, &runs[0]);
+    assert_eq!(first.len(), 2);
+    assert!(first[1].starts_with("Part 1 | 24000 ") && first[1].ends_with("|       20 µs"));
+
+    let current = Run { part1: Some(String::from("45000")), parse_us: Some(5), part1_us: Some(20), ..Run::default() };
+    let lines = diff(Some(&runs[0]), &current);
+    assert!(lines[0].ends_with("5 µs (-50.0%)"));
+    assert!(lines[1].contains("45000            (was 24000)") && lines[1].ends_with("(+0.0%)"));
+  }
+}
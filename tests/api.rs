@@ -0,0 +1,70 @@
+use std::path::Path;
+
+use aoc2022::answers::{check, Answers, Check};
+use aoc2022::day11::parse_monkeys;
+use aoc2022::day12::create_graph;
+use aoc2022::output::Record;
+use aoc2022::solution::run;
+use aoc2022::utils::Input;
+use aoc2022::{select, solver, SOLVERS};
+
+const HEIGHTMAP: &str = "Sabqponm\nabcryxxl\naccszExk\nacctuvwj\nabdefghi\n";
+
+#[test]
+fn test_registry() {
+  assert_eq!(SOLVERS.iter().map(|s| s.day()).collect::<Vec<_>>(), (1..=SOLVERS.len()).collect::<Vec<_>>());
+  assert_eq!(solver(12).map(|s| s.title()), Some("Hill Climbing Algorithm"));
+  assert!(solver(0).is_none());
+}
+
+#[test]
+fn test_run_solver() {
+  let report = run(solver(12).unwrap(), HEIGHTMAP).unwrap();
+  assert_eq!((report.part1.as_deref(), report.part2.as_deref()), (Some("31"), Some("29")));
+}
+
+#[test]
+fn test_day_helpers() {
+  let graph = create_graph(HEIGHTMAP).unwrap();
+  assert_eq!((graph.heights.width(), graph.heights.height(), graph.start, graph.end), (8, 5, (0, 0), (5, 2)));
+
+  let monkeys = parse_monkeys("Monkey 0:\n  Starting items: 79, 98\n  Operation: new = old * 19\n  Test: divisible by 23\n    If true: throw to monkey 2\n    If false: throw to monkey 3\n").unwrap();
+  assert_eq!(monkeys.len(), 1);
+  assert_eq!(monkeys[0].items, [79, 98]);
+
+  let error = create_graph("Sab\nabE\nab").unwrap_err();
+  assert_eq!(error.to_string(), "<input>:3:1: Expected a row of 3 cells, found \"ab\"");
+}
+
+#[test]
+fn test_bad_input_reports_location() {
+  let error = run(solver(4).unwrap(), "2-4,6-8\n2-3,4_5\n").unwrap_err().in_file("day4.txt");
+  assert_eq!(error.to_string(), "day4.txt:2:5: Expected a range like 2-4, found \"4_5\"");
+
+  assert!(run(solver(12).unwrap(), "abc\n").is_err());
+}
+
+#[test]
+fn test_examples() {
+  let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("input").join("examples");
+  let answers = Answers::load(&examples.join("answers.toml")).unwrap();
+  let input = Input::Path(examples);
+
+  for solver in SOLVERS {
+    let result = input.read(solver.day()).and_then(|string| run(*solver, &string));
+    let record = Record::new(*solver, result);
+
+    assert_eq!(check(&record, &answers), (Check::Pass, Check::Pass), "Day {}", solver.day());
+  }
+}
+
+#[test]
+fn test_select() {
+  let days = |spec: &str| select(spec).map(|solvers| solvers.iter().map(|s| s.day()).collect::<Vec<_>>());
+
+  assert_eq!(days("3,7,11-13"), Ok(vec![3, 7, 11, 12, 13]));
+  assert_eq!(days("5-6,5"), Ok(vec![5, 6]));
+  assert!(days("13-11").is_err());
+  assert!(days("3,x").is_err());
+  assert!(days("0").is_err());
+}
@@ -0,0 +1,40 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Runs the binary with `args`, writing `stdin` to it. Returns whether it
+/// succeeded, with its stdout and stderr.
+fn run(args: &[&str], stdin: &str) -> (bool, String, String) {
+  let mut child = Command::new(env!("CARGO_BIN_EXE_aoc2022"))
+    .args(args)
+    .env("NO_COLOR", "1")
+    .env_remove("AOC_SESSION")
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+    .unwrap();
+
+  child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+  let output = child.wait_with_output().unwrap();
+
+  (output.status.success(), String::from_utf8(output.stdout).unwrap(), String::from_utf8(output.stderr).unwrap())
+}
+
+#[test]
+fn test_render_from_stdin() {
+  let sample = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/input/examples/day12.txt")).unwrap();
+  let (success, out, err) = run(&["--input", "-", "day", "12", "--render"], &sample);
+
+  assert!(success, "{err}");
+  assert!(err.is_empty(), "{err}");
+  assert!(out.contains("Day 12: Hill Climbing Algorithm"), "{out}");
+  assert!(out.contains("Part 1: 31 steps"), "{out}");
+}
+
+#[test]
+fn test_render_failure() {
+  let (success, _, err) = run(&["--input", "-", "day", "12", "--render"], "Sab\nabE\nab\n");
+
+  assert!(!success);
+  assert!(err.contains("Expected a row of 3 cells"), "{err}");
+}